@@ -0,0 +1,110 @@
+use std::{error::Error, fmt::Debug, fmt::Display, hash::Hash, str::FromStr};
+
+/// The integer types a location ID list can be made of.
+/// All the arithmetic is checked, giving None rather than wrapping or panicking when the answer doesn't fit.
+pub trait LocationId: Copy + Ord + Hash + Display + Debug {
+    const ZERO: Self;
+
+    fn parse(s: &str) -> Result<Self, Box<dyn Error>>;
+
+    /// How far apart two IDs are, always zero or more
+    fn distance(self, other: Self) -> Option<Self>;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    fn checked_mul(self, other: Self) -> Option<Self>;
+
+    fn from_count(count: usize) -> Option<Self>;
+
+    /// Approximately, for statistics
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! unsigned_location_id {
+    ($($t:ty),*) => {
+        $(
+            impl LocationId for $t {
+                const ZERO: Self = 0;
+
+                fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
+                    Ok(<$t>::from_str(s)?)
+                }
+
+                fn distance(self, other: Self) -> Option<Self> {
+                    Some(self.abs_diff(other))
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                fn from_count(count: usize) -> Option<Self> {
+                    Self::try_from(count).ok()
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! signed_location_id {
+    ($($t:ty),*) => {
+        $(
+            impl LocationId for $t {
+                const ZERO: Self = 0;
+
+                fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
+                    Ok(<$t>::from_str(s)?)
+                }
+
+                fn distance(self, other: Self) -> Option<Self> {
+                    // the difference between two signed values can be too big for the type even when both fit
+                    self.checked_sub(other).and_then(|d| d.checked_abs())
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                fn from_count(count: usize) -> Option<Self> {
+                    Self::try_from(count).ok()
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+unsigned_location_id!(u8, u16, u32, u64, u128, usize);
+signed_location_id!(i8, i16, i32, i64, i128, isize);
+
+#[test]
+fn test_distance() {
+    assert_eq!(3u32.distance(7), Some(4));
+    assert_eq!(7u32.distance(3), Some(4));
+    assert_eq!((-5i64).distance(3), Some(8));
+    assert_eq!(i8::MIN.distance(0), None);
+    assert_eq!(i8::MAX.distance(i8::MIN), None);
+    assert_eq!(u128::MAX.distance(0), Some(u128::MAX));
+}
+
+#[test]
+fn test_from_count() {
+    assert_eq!(u8::from_count(255), Some(255));
+    assert_eq!(u8::from_count(256), None);
+    assert_eq!(i64::from_count(3), Some(3));
+}
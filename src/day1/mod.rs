@@ -1,11 +1,42 @@
-use std::{collections::HashMap, error::Error, str::FromStr};
+use std::{collections::HashMap, error::Error, io::BufRead};
+
+use location_id::LocationId;
+use report::ListReport;
+
+use crate::options::{has_flag, parsed_value_of, value_of};
+
+mod location_id;
+mod report;
 
 // https://adventofcode.com/2024/day/1
 
-pub fn run() -> Result<(), Box<dyn Error>> {
+/// Options:
+///   --report              print a breakdown of where both answers come from instead of just the answers
+///   --top N               how many of the largest distances the report shows (default 10)
+///   --buckets N           how many buckets the report's distance histogram has (default 10)
+///   --csv pairs|similarity  print every sorted pair or every left value's similarity contribution as CSV
+pub fn run(options: &[String]) -> Result<(), Box<dyn Error>> {
     let input = include_str!("../inputs/day1.txt");
 
-    let (first, second) = parse_lists(input)?;
+    let (first, second) = parse_lists::<i64>(input.as_bytes())?;
+
+    if let Some(table) = value_of(options, "--csv")? {
+        let report = ListReport::new(first, second)?;
+        match table {
+            "pairs" => print!("{}", report.pairs_csv()),
+            "similarity" => print!("{}", report.similarity_csv()),
+            _ => return Err(format!("Unknown CSV table '{}'", table).into()),
+        }
+        return Ok(());
+    }
+
+    if has_flag(options, "--report") {
+        let report = ListReport::new(first, second)?;
+        let top = parsed_value_of(options, "--top")?.unwrap_or(10);
+        let buckets = parsed_value_of(options, "--buckets")?.unwrap_or(10);
+        print!("{}", report.summary(top, buckets));
+        return Ok(());
+    }
 
     let part2 = part2(&first, &second)?;
     let part1 = part1(first, second)?;
@@ -16,65 +47,82 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn parse_lists(input: &str) -> Result<(Vec<u32>, Vec<u32>), Box<dyn Error>> {
-    let pairs = input
-        .lines()
-        .map(|l| {
-            let mut parts = l.split_whitespace();
-            if let Some(first) = parts.next() {
-                if let Some(second) = parts.next() {
-                    Ok((u32::from_str(first)?, u32::from_str(second)?))
-                } else {
-                    Err(format!("Line {} didn't have a second part", l).into())
-                }
+/// Read the two lists a line at a time from any buffered source
+fn parse_lists<T: LocationId>(mut input: impl BufRead) -> Result<(Vec<T>, Vec<T>), Box<dyn Error>> {
+    let mut first_list = Vec::new();
+    let mut second_list = Vec::new();
+    let mut line = String::new();
+
+    while input.read_line(&mut line)? > 0 {
+        let l = line.trim_end_matches(['\r', '\n']);
+        let mut parts = l.split_whitespace();
+        if let Some(first) = parts.next() {
+            if let Some(second) = parts.next() {
+                first_list.push(T::parse(first)?);
+                second_list.push(T::parse(second)?);
             } else {
-                Err(format!("Line {} didn't have a first part", l).into())
+                return Err(format!("Line {} didn't have a second part", l).into());
             }
-        })
-        .collect::<Result<Vec<(u32, u32)>, Box<dyn Error>>>()?;
+        } else {
+            return Err(format!("Line {} didn't have a first part", l).into());
+        }
+        line.clear();
+    }
 
-    Ok(pairs.into_iter().unzip())
+    Ok((first_list, second_list))
 }
 
-fn make_sorted_pairs(
-    mut first: Vec<u32>,
-    mut second: Vec<u32>,
-) -> Result<Vec<(u32, u32)>, Box<dyn Error>> {
+fn make_sorted_pairs<T: LocationId>(
+    mut first: Vec<T>,
+    mut second: Vec<T>,
+) -> Result<Vec<(T, T)>, Box<dyn Error>> {
     first.sort();
     second.sort();
 
     Ok(first.into_iter().zip(second).collect())
 }
 
-fn pair_distance(pair: &(u32, u32)) -> u32 {
-    if pair.0 < pair.1 {
-        pair.1 - pair.0
-    } else {
-        pair.0 - pair.1
-    }
+fn pair_distance<T: LocationId>(pair: &(T, T)) -> Result<T, Box<dyn Error>> {
+    pair.0
+        .distance(pair.1)
+        .ok_or_else(|| format!("Distance between {} and {} overflowed", pair.0, pair.1).into())
 }
 
-fn part1(first: Vec<u32>, second: Vec<u32>) -> Result<u32, Box<dyn Error>> {
+fn checked_sum<T: LocationId>(
+    mut values: impl Iterator<Item = Result<T, Box<dyn Error>>>,
+) -> Result<T, Box<dyn Error>> {
+    values.try_fold(T::ZERO, |total, v| {
+        let v = v?;
+        total
+            .checked_add(v)
+            .ok_or_else(|| format!("Adding {} to {} overflowed", v, total).into())
+    })
+}
+
+fn part1<T: LocationId>(first: Vec<T>, second: Vec<T>) -> Result<T, Box<dyn Error>> {
     let pairs = make_sorted_pairs(first, second)?;
-    Ok(pairs.iter().map(pair_distance).sum())
+    checked_sum(pairs.iter().map(pair_distance))
 }
 
-fn occurrance_map(i: &[u32]) -> HashMap<u32, u32> {
+fn occurrance_map<T: LocationId>(i: &[T]) -> HashMap<T, usize> {
     let mut map = HashMap::new();
 
     for n in i.iter() {
-        let times_in_list = i.iter().filter(|x| *x == n).count() as u32;
-        map.insert(*n, times_in_list);
+        *map.entry(*n).or_insert(0) += 1;
     }
 
     map
 }
 
-fn part2(left: &[u32], right: &[u32]) -> Result<u32, Box<dyn Error>> {
+fn part2<T: LocationId>(left: &[T], right: &[T]) -> Result<T, Box<dyn Error>> {
     let occ_map = occurrance_map(right);
 
-    let similarity_score = left.iter().map(|l| l * occ_map.get(l).unwrap_or(&0)).sum();
-    Ok(similarity_score)
+    checked_sum(left.iter().map(|l| {
+        let count = occ_map.get(l).cloned().unwrap_or(0);
+        T::from_count(count)
+            .and_then(|c| l.checked_mul(c))
+            .ok_or_else(|| format!("{} appearing {} times overflowed", l, count).into())
+    }))
 }
 
 #[test]
@@ -85,7 +133,7 @@ fn test_part1() {
 1   3
 3   9
 3   3";
-    let (first, second) = parse_lists(input).expect("No errors here please");
+    let (first, second) = parse_lists::<u32>(input.as_bytes()).expect("No errors here please");
     let answer = part1(first, second).expect("No errors here please");
     assert_eq!(answer, 11);
 }
@@ -109,6 +157,65 @@ fn test_part2() {
 3   9
 3   3";
 
-    let (first, second) = parse_lists(input).expect("No errors here please");
+    let (first, second) = parse_lists::<u32>(input.as_bytes()).expect("No errors here please");
     assert_eq!(part2(&first, &second).expect("No errors wanted"), 31);
 }
+
+#[cfg(test)]
+use std::fmt::Write;
+
+#[test]
+#[ignore = "takes about 10 seconds in a debug build, run it with `cargo test --release -- --ignored`"]
+fn test_part2_millions_of_pairs() {
+    // the right list is the left list shifted up so only the last ten values are shared,
+    // which keeps the answer small while still making a quadratic count take forever
+    const PAIRS: u32 = 2_000_000;
+    const SHIFT: u32 = PAIRS - 10;
+
+    let mut input = String::new();
+    for i in 0..PAIRS {
+        writeln!(input, "{}   {}", i, i + SHIFT).expect("Writing to a string can't fail");
+    }
+
+    let (first, second) = parse_lists::<u32>(input.as_bytes()).expect("No errors here please");
+    assert_eq!(first.len(), PAIRS as usize);
+    assert_eq!(
+        part2(&first, &second).expect("No errors wanted"),
+        (SHIFT..PAIRS).sum::<u32>()
+    );
+}
+
+#[test]
+fn test_signed_and_wide_ids() {
+    let input = "-3   4
+4   -3
+2   5";
+    let (first, second) = parse_lists::<i64>(input.as_bytes()).expect("No errors here please");
+    assert_eq!(part1(first.clone(), second.clone()).unwrap(), 3);
+    assert_eq!(part2(&first, &second).unwrap(), 1);
+    assert!(parse_lists::<u32>(input.as_bytes()).is_err());
+
+    let input = "5000000000   5000000001
+5000000001   5000000001";
+    let (first, second) = parse_lists::<u128>(input.as_bytes()).expect("No errors here please");
+    assert_eq!(part1(first.clone(), second.clone()).unwrap(), 1);
+    assert_eq!(part2(&first, &second).unwrap(), 10000000002);
+    assert!(parse_lists::<u32>(input.as_bytes()).is_err());
+}
+
+#[test]
+fn test_overflow_is_an_error() {
+    let input = "4000000000   4000000000
+4000000000   4000000000";
+    let (first, second) = parse_lists::<u32>(input.as_bytes()).expect("No errors here please");
+    assert!(part2(&first, &second).is_err());
+
+    let input = "0   4000000000
+0   4000000000";
+    let (first, second) = parse_lists::<u32>(input.as_bytes()).expect("No errors here please");
+    assert!(part1(first, second).is_err());
+
+    let input = "-9223372036854775808   1";
+    let (first, second) = parse_lists::<i64>(input.as_bytes()).expect("No errors here please");
+    assert!(part1(first, second).is_err());
+}
@@ -0,0 +1,295 @@
+use std::{error::Error, fmt::Display};
+
+use super::{
+    checked_sum, location_id::LocationId, make_sorted_pairs, occurrance_map, pair_distance,
+};
+
+/// One pair from the sorted lists, as used for part 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairRow<T> {
+    pub left: T,
+    pub right: T,
+    pub distance: T,
+}
+
+/// One value from the left list and what it adds to the part 2 similarity score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarityRow<T> {
+    pub left: T,
+    pub occurrences: usize,
+    pub contribution: T,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBucket {
+    /// Inclusive
+    pub from: f64,
+    /// Exclusive, except for the last bucket
+    pub to: f64,
+    pub count: usize,
+}
+
+/// Everything that goes into both parts' answers, for checking where they came from
+pub struct ListReport<T> {
+    pub pairs: Vec<PairRow<T>>,
+    pub similarity: Vec<SimilarityRow<T>>,
+    pub total_distance: T,
+    pub similarity_score: T,
+}
+
+impl<T: LocationId> ListReport<T> {
+    pub fn new(first: Vec<T>, second: Vec<T>) -> Result<Self, Box<dyn Error>> {
+        let occurrences = occurrance_map(&second);
+
+        let pairs = make_sorted_pairs(first, second)?
+            .into_iter()
+            .map(|pair| {
+                Ok(PairRow {
+                    left: pair.0,
+                    right: pair.1,
+                    distance: pair_distance(&pair)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        let similarity = pairs
+            .iter()
+            .map(|p| {
+                let occurrences = occurrences.get(&p.left).cloned().unwrap_or(0);
+                let contribution = T::from_count(occurrences)
+                    .and_then(|c| p.left.checked_mul(c))
+                    .ok_or_else(|| {
+                        format!("{} appearing {} times overflowed", p.left, occurrences)
+                    })?;
+                Ok(SimilarityRow {
+                    left: p.left,
+                    occurrences,
+                    contribution,
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        let total_distance = checked_sum(pairs.iter().map(|p| Ok(p.distance)))?;
+        let similarity_score = checked_sum(similarity.iter().map(|s| Ok(s.contribution)))?;
+
+        Ok(ListReport {
+            pairs,
+            similarity,
+            total_distance,
+            similarity_score,
+        })
+    }
+
+    fn sorted_distances(&self) -> Vec<T> {
+        let mut distances = self.pairs.iter().map(|p| p.distance).collect::<Vec<_>>();
+        distances.sort();
+        distances
+    }
+
+    /// The `n` pairs furthest apart, furthest first
+    pub fn top_distances(&self, n: usize) -> Vec<&PairRow<T>> {
+        let mut pairs = self.pairs.iter().collect::<Vec<_>>();
+        pairs.sort_by_key(|p| std::cmp::Reverse(p.distance));
+        pairs.truncate(n);
+        pairs
+    }
+
+    /// The distance at the given quantile (0.0 to 1.0) by the nearest-rank method, or None if there are no pairs
+    pub fn quantile(&self, q: f64) -> Option<T> {
+        let distances = self.sorted_distances();
+        if distances.is_empty() {
+            return None;
+        }
+        let rank = (q.clamp(0.0, 1.0) * distances.len() as f64).ceil() as usize;
+        Some(distances[rank.saturating_sub(1)])
+    }
+
+    /// Counts of distances in `buckets` equal-width ranges running from the smallest distance to the largest
+    pub fn histogram(&self, buckets: usize) -> Vec<HistogramBucket> {
+        let distances = self.sorted_distances();
+        let (Some(min), Some(max)) = (distances.first(), distances.last()) else {
+            return Vec::new();
+        };
+        if buckets == 0 {
+            return Vec::new();
+        }
+
+        let (min, max) = (min.to_f64(), max.to_f64());
+        let width = ((max - min) / buckets as f64).max(f64::MIN_POSITIVE);
+        let mut histogram = (0..buckets)
+            .map(|b| HistogramBucket {
+                from: min + width * b as f64,
+                to: min + width * (b + 1) as f64,
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+
+        for d in distances {
+            let bucket = (((d.to_f64() - min) / width) as usize).min(buckets - 1);
+            histogram[bucket].count += 1;
+        }
+
+        histogram
+    }
+
+    pub fn pairs_csv(&self) -> String {
+        let mut csv = String::from("left,right,distance\n");
+        for p in self.pairs.iter() {
+            csv.push_str(&format!("{},{},{}\n", p.left, p.right, p.distance));
+        }
+        csv
+    }
+
+    pub fn similarity_csv(&self) -> String {
+        let mut csv = String::from("left,occurrences,contribution\n");
+        for s in self.similarity.iter() {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                s.left, s.occurrences, s.contribution
+            ));
+        }
+        csv
+    }
+
+    /// Summary of the report with the given number of largest distances and histogram buckets
+    pub fn summary(&self, top: usize, buckets: usize) -> ReportSummary<'_, T> {
+        ReportSummary {
+            report: self,
+            top,
+            buckets,
+        }
+    }
+}
+
+pub struct ReportSummary<'a, T> {
+    report: &'a ListReport<T>,
+    top: usize,
+    buckets: usize,
+}
+
+impl<T: LocationId> Display for ReportSummary<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let report = self.report;
+        writeln!(f, "{} pairs", report.pairs.len())?;
+        writeln!(f, "Total distance {}", report.total_distance)?;
+        writeln!(f, "Similarity score {}", report.similarity_score)?;
+
+        writeln!(f, "\nLargest {} distances:", self.top)?;
+        for p in report.top_distances(self.top) {
+            writeln!(f, "  {} - {} = {}", p.left, p.right, p.distance)?;
+        }
+
+        writeln!(f, "\nDistance quantiles:")?;
+        for q in [0.0, 0.25, 0.5, 0.75, 0.9, 0.99, 1.0] {
+            if let Some(d) = report.quantile(q) {
+                writeln!(f, "  {:>4}: {}", q, d)?;
+            }
+        }
+
+        writeln!(f, "\nDistance histogram:")?;
+        let histogram = report.histogram(self.buckets);
+        let biggest = histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+        for b in histogram {
+            writeln!(
+                f,
+                "  {:>12.1} - {:<12.1} {:>6} {}",
+                b.from,
+                b.to,
+                b.count,
+                "#".repeat(b.count * 50 / biggest)
+            )?;
+        }
+
+        let contributors = report
+            .similarity
+            .iter()
+            .filter(|s| s.occurrences > 0)
+            .count();
+        writeln!(
+            f,
+            "\n{} of {} left values appear in the right list",
+            contributors,
+            report.similarity.len()
+        )
+    }
+}
+
+#[cfg(test)]
+const TEST_INPUT: &str = "3   4
+4   3
+2   5
+1   3
+3   9
+3   3";
+
+#[cfg(test)]
+fn test_report() -> ListReport<u32> {
+    let (first, second) =
+        super::parse_lists::<u32>(TEST_INPUT.as_bytes()).expect("No errors here please");
+    ListReport::new(first, second).expect("No errors here please")
+}
+
+#[test]
+fn test_report_totals_match_parts() {
+    let report = test_report();
+    assert_eq!(report.total_distance, 11);
+    assert_eq!(report.similarity_score, 31);
+    assert_eq!(
+        report.pairs[0],
+        PairRow {
+            left: 1,
+            right: 3,
+            distance: 2
+        }
+    );
+    assert_eq!(
+        report.similarity[2],
+        SimilarityRow {
+            left: 3,
+            occurrences: 3,
+            contribution: 9
+        }
+    );
+}
+
+#[test]
+fn test_top_distances_and_quantiles() {
+    let report = test_report();
+    let top = report
+        .top_distances(2)
+        .iter()
+        .map(|p| p.distance)
+        .collect::<Vec<_>>();
+    assert_eq!(top, vec![5, 2]);
+
+    // sorted distances are 0 1 1 2 2 5
+    assert_eq!(report.quantile(0.0), Some(0));
+    assert_eq!(report.quantile(0.5), Some(1));
+    assert_eq!(report.quantile(0.9), Some(5));
+    assert_eq!(report.quantile(1.0), Some(5));
+}
+
+#[test]
+fn test_histogram() {
+    let report = test_report();
+    let histogram = report.histogram(5);
+    assert_eq!(
+        histogram.iter().map(|b| b.count).collect::<Vec<_>>(),
+        vec![1, 2, 2, 0, 1]
+    );
+    assert_eq!(histogram[0].from, 0.0);
+    assert_eq!(histogram[4].to, 5.0);
+}
+
+#[test]
+fn test_csv() {
+    let report = test_report();
+    let csv = report.pairs_csv();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("left,right,distance"));
+    assert_eq!(lines.next(), Some("1,3,2"));
+    assert_eq!(lines.count(), 5);
+
+    let csv = report.similarity_csv();
+    assert_eq!(csv.lines().nth(3), Some("3,3,9"));
+}
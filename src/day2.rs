@@ -1,14 +1,48 @@
 use itertools::Itertools;
 use std::error::Error;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-pub fn run() -> Result<(), Box<dyn Error>> {
+use crate::options::{has_flag, parsed_value_of, value_of};
+
+/// Options, any of which switch from the two puzzle answers to a single count under the given policy:
+///   --min-step N          smallest allowed difference between neighbouring levels (default 1)
+///   --max-step N          largest allowed difference between neighbouring levels (default 3)
+///   --allow-equal         neighbouring levels may be the same
+///   --direction ascending|descending|either  which way the levels have to go (default either)
+///   --max-removals N      how many levels the problem dampener may remove (default 0)
+///   --explain             list every unsafe report and why, under the given policy or the part 2 rules
+pub fn run(options: &[String]) -> Result<(), Box<dyn Error>> {
     let input = include_str!("inputs/day2.txt");
     let reports = input
         .lines()
         .map(Report::from_str)
         .collect::<Result<Vec<_>, _>>()?;
 
+    let policy = SafetyPolicy::from_options(options)?;
+
+    if has_flag(options, "--explain") {
+        let policy = policy.unwrap_or_else(SafetyPolicy::dampened);
+        for (line, report) in reports.iter().enumerate() {
+            if let Some(explanation) = report.explain(&policy) {
+                println!("Report {} ({}):", line + 1, report.levels.iter().join(" "));
+                print!("{}", explanation);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(policy) = policy {
+        println!(
+            "{} out of {} reports are safe {}",
+            count_safe(&reports, &policy),
+            reports.len(),
+            policy
+        );
+        return Ok(());
+    }
+
     let part1 = part1(&reports);
 
     println!(
@@ -27,12 +61,212 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn count_safe(reports: &[Report], policy: &SafetyPolicy) -> usize {
+    reports.iter().filter(|r| r.is_safe(policy)).count()
+}
+
 fn part1(reports: &[Report]) -> usize {
-    reports.iter().filter(|r| r.is_safe()).count()
+    count_safe(reports, &SafetyPolicy::default())
 }
 
 fn part2(reports: &[Report]) -> usize {
-    reports.iter().filter(|r| r.is_safe_when_dampened()).count()
+    count_safe(reports, &SafetyPolicy::dampened())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LevelDirection {
+    Ascending,
+    Descending,
+}
+
+/// What makes a report safe. The default is the part 1 rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SafetyPolicy {
+    /// How far apart neighbouring levels are allowed to be when they aren't equal
+    steps: RangeInclusive<u32>,
+    /// Whether neighbouring levels may be equal, whatever `steps` says
+    allow_equal: bool,
+    /// Which way the levels have to go, or None if either way will do as long as it's the same all along
+    direction: Option<LevelDirection>,
+    /// How many levels the problem dampener may remove to make a report safe
+    max_removals: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy {
+            steps: 1..=3,
+            allow_equal: false,
+            direction: None,
+            max_removals: 0,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    /// The part 2 rules
+    fn dampened() -> Self {
+        SafetyPolicy {
+            max_removals: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The policy described by the command line options, starting from the default,
+    /// or None if no policy options were given
+    fn from_options(options: &[String]) -> Result<Option<Self>, Box<dyn Error>> {
+        let default = SafetyPolicy::default();
+        let min_step = parsed_value_of(options, "--min-step")?;
+        let max_step = parsed_value_of(options, "--max-step")?;
+        let allow_equal = has_flag(options, "--allow-equal");
+        let direction = value_of(options, "--direction")?;
+        let max_removals = parsed_value_of(options, "--max-removals")?;
+
+        if min_step.is_none()
+            && max_step.is_none()
+            && !allow_equal
+            && direction.is_none()
+            && max_removals.is_none()
+        {
+            return Ok(None);
+        }
+
+        let steps =
+            min_step.unwrap_or(*default.steps.start())..=max_step.unwrap_or(*default.steps.end());
+        if steps.is_empty() {
+            return Err(format!(
+                "Minimum step {} is bigger than maximum step {}",
+                steps.start(),
+                steps.end()
+            )
+            .into());
+        }
+
+        let direction = match direction {
+            None | Some("either") => None,
+            Some("ascending") => Some(LevelDirection::Ascending),
+            Some("descending") => Some(LevelDirection::Descending),
+            Some(d) => return Err(format!("Direction '{}' is not understood", d).into()),
+        };
+
+        Ok(Some(SafetyPolicy {
+            steps,
+            allow_equal,
+            direction,
+            max_removals: max_removals.unwrap_or(default.max_removals),
+        }))
+    }
+
+    fn allowed_directions(&self) -> &'static [LevelDirection] {
+        match self.direction {
+            None => &[LevelDirection::Ascending, LevelDirection::Descending],
+            Some(LevelDirection::Ascending) => &[LevelDirection::Ascending],
+            Some(LevelDirection::Descending) => &[LevelDirection::Descending],
+        }
+    }
+
+    /// Whether going from level `a` to level `b` is allowed when the report is going in the given direction
+    fn allows_step(&self, a: u32, b: u32, direction: LevelDirection) -> bool {
+        if a == b {
+            return self.allow_equal;
+        }
+        let step = match direction {
+            LevelDirection::Ascending => b.checked_sub(a),
+            LevelDirection::Descending => a.checked_sub(b),
+        };
+        step.is_some_and(|s| self.steps.contains(&s))
+    }
+}
+
+impl Display for SafetyPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "with steps of {} to {}{}, going {}, removing up to {} levels",
+            self.steps.start(),
+            self.steps.end(),
+            if self.allow_equal { " or equal" } else { "" },
+            match self.direction {
+                None => "either way",
+                Some(LevelDirection::Ascending) => "up",
+                Some(LevelDirection::Descending) => "down",
+            },
+            self.max_removals
+        )
+    }
+}
+
+/// Something wrong with a pair of neighbouring levels, the ones at `index` and `index + 1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UnsafeReason {
+    /// Going the opposite way to the rest of the report, or to the way the policy requires
+    DirectionChange {
+        index: usize,
+    },
+    StepTooLarge {
+        index: usize,
+        step: u32,
+    },
+    StepTooSmall {
+        index: usize,
+        step: u32,
+    },
+    DuplicateLevel {
+        index: usize,
+    },
+}
+
+/// Levels are numbered from 1 when they're shown, like the reports are
+impl Display for UnsafeReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsafeReason::DirectionChange { index } => {
+                write!(f, "levels {} and {} change direction", index + 1, index + 2)
+            }
+            UnsafeReason::StepTooLarge { index, step } => write!(
+                f,
+                "step of {} between levels {} and {} is too large",
+                step,
+                index + 1,
+                index + 2
+            ),
+            UnsafeReason::StepTooSmall { index, step } => write!(
+                f,
+                "step of {} between levels {} and {} is too small",
+                step,
+                index + 1,
+                index + 2
+            ),
+            UnsafeReason::DuplicateLevel { index } => {
+                write!(f, "levels {} and {} are the same", index + 1, index + 2)
+            }
+        }
+    }
+}
+
+/// Why a report isn't safe as it stands
+#[derive(Debug, PartialEq, Eq)]
+struct Explanation {
+    reasons: Vec<UnsafeReason>,
+    /// The levels the problem dampener removes to make it safe, if it can
+    fix: Option<Vec<usize>>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for reason in self.reasons.iter() {
+            writeln!(f, "  {}", reason)?;
+        }
+        match &self.fix {
+            Some(fix) => writeln!(
+                f,
+                "  safe after removing {} {}",
+                if fix.len() == 1 { "level" } else { "levels" },
+                fix.iter().map(|i| i + 1).join(", ")
+            ),
+            None => writeln!(f, "  can't be made safe"),
+        }
+    }
 }
 
 struct Report {
@@ -40,55 +274,155 @@ struct Report {
 }
 
 impl Report {
-    fn is_safe(&self) -> bool {
-        Self::is_safe_impl(&self.levels)
+    fn is_safe(&self, policy: &SafetyPolicy) -> bool {
+        self.levels_to_remove(policy).is_some()
     }
 
-    fn is_safe_impl(levels: &[u32]) -> bool {
-        let ascending = Self::is_ascending(levels);
-        if !ascending {
-            let descending = Self::is_descending(levels);
-            if !descending {
-                // neither ascending nor descending; not safe
-                return false;
-            }
+    /// The fewest levels the problem dampener has to remove to make the report safe, as indices into the levels,
+    /// or None if it would take more than the policy allows. Safe reports need nothing removed.
+    fn levels_to_remove(&self, policy: &SafetyPolicy) -> Option<Vec<usize>> {
+        if Self::is_safe_impl(&self.levels, policy) {
+            return Some(Vec::new());
         }
 
-        levels
+        policy
+            .allowed_directions()
             .iter()
-            .tuple_windows()
-            .map(|(a, b)| if ascending { b - a } else { a - b })
-            .all(|x| (1..=3).contains(&x))
+            .filter_map(|direction| Self::levels_to_remove_going(&self.levels, policy, *direction))
+            .min_by_key(|removed| removed.len())
     }
 
-    fn is_ascending(levels: &[u32]) -> bool {
-        let mut sorted = levels.to_vec();
-        sorted.sort();
-        sorted == levels
-    }
+    /// Works along the levels keeping track, for each level, of the fewest removals that leave a safe run
+    /// ending with that level, and which level was kept before it.
+    /// A run can't jump over more than `max_removals` levels, so only that many earlier levels are worth
+    /// looking back at, making this O(n·k) rather than trying every combination of removals.
+    fn levels_to_remove_going(
+        levels: &[u32],
+        policy: &SafetyPolicy,
+        direction: LevelDirection,
+    ) -> Option<Vec<usize>> {
+        let max_removals = policy.max_removals;
+        let mut best: Vec<Option<(usize, Option<usize>)>> = vec![None; levels.len()];
+
+        for i in 0..levels.len() {
+            // keeping this as the first level means removing everything before it
+            let mut candidate = (i <= max_removals).then_some((i, None));
+            for j in i.saturating_sub(max_removals + 1)..i {
+                let Some((removed_before, _)) = best[j] else {
+                    continue;
+                };
+                let removed = removed_before + (i - j - 1);
+                if removed <= max_removals
+                    && policy.allows_step(levels[j], levels[i], direction)
+                    && candidate.is_none_or(|(c, _)| removed < c)
+                {
+                    candidate = Some((removed, Some(j)));
+                }
+            }
+            best[i] = candidate;
+        }
 
-    fn is_descending(levels: &[u32]) -> bool {
-        let mut sorted = levels.to_vec();
-        sorted.sort_by(|a, b| b.cmp(a));
-        sorted == levels
+        // everything after the last kept level gets removed too
+        let last = (levels.len().saturating_sub(max_removals + 1)..levels.len())
+            .filter_map(|i| best[i].map(|(removed, _)| (i, removed + levels.len() - 1 - i)))
+            .filter(|(_, removed)| *removed <= max_removals)
+            .min_by_key(|(_, removed)| *removed);
+
+        let Some((last, _)) = last else {
+            // only an empty report has no last level and is still safe
+            return levels.is_empty().then(Vec::new);
+        };
+
+        let mut kept = vec![false; levels.len()];
+        let mut at = Some(last);
+        while let Some(i) = at {
+            kept[i] = true;
+            at = best[i].and_then(|(_, previous)| previous);
+        }
+
+        Some((0..levels.len()).filter(|i| !kept[*i]).collect())
     }
 
-    fn is_safe_when_dampened(&self) -> bool {
-        if self.is_safe() {
-            return true;
+    /// Why the report isn't safe without removing anything, or None if it is.
+    /// When the policy doesn't say which way the levels go, they're taken to go whichever way most of the steps do.
+    fn explain(&self, policy: &SafetyPolicy) -> Option<Explanation> {
+        if Self::is_safe_impl(&self.levels, policy) {
+            return None;
         }
 
-        // okay so it's unsafe
-        // but we're allowed to declare it safe by removing one single level, if the remaining levels evaluate to safe
-        // so we're going to try all of the possibilities until we either get a safe one or we run out of them
-        for index_to_remove in 0..self.levels.len() {
-            let mut new_levels = self.levels.clone();
-            new_levels.remove(index_to_remove);
-            if Self::is_safe_impl(&new_levels) {
-                return true;
+        let direction = policy.direction.unwrap_or_else(|| {
+            let (up, down) =
+                self.levels
+                    .iter()
+                    .tuple_windows()
+                    .fold((0, 0), |(up, down), (a, b)| match a.cmp(b) {
+                        std::cmp::Ordering::Less => (up + 1, down),
+                        std::cmp::Ordering::Greater => (up, down + 1),
+                        std::cmp::Ordering::Equal => (up, down),
+                    });
+            if up >= down {
+                LevelDirection::Ascending
+            } else {
+                LevelDirection::Descending
             }
-        }
-        false
+        });
+
+        let reasons = self
+            .levels
+            .iter()
+            .tuple_windows()
+            .enumerate()
+            .filter_map(|(index, (a, b))| {
+                if policy.allows_step(*a, *b, direction) {
+                    return None;
+                }
+                let step = a.abs_diff(*b);
+                let going_up = b > a;
+                Some(if step == 0 {
+                    UnsafeReason::DuplicateLevel { index }
+                } else if going_up != (direction == LevelDirection::Ascending) {
+                    UnsafeReason::DirectionChange { index }
+                } else if step > *policy.steps.end() {
+                    UnsafeReason::StepTooLarge { index, step }
+                } else {
+                    UnsafeReason::StepTooSmall { index, step }
+                })
+            })
+            .collect();
+
+        Some(Explanation {
+            reasons,
+            fix: self.levels_to_remove(policy),
+        })
+    }
+
+    /// Whether the levels are safe as they are, without removing anything
+    fn is_safe_impl(levels: &[u32], policy: &SafetyPolicy) -> bool {
+        policy.allowed_directions().iter().any(|direction| {
+            levels
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| policy.allows_step(*a, *b, *direction))
+        })
+    }
+
+    /// The fewest removals needed by trying every combination
+    #[cfg(test)]
+    fn fewest_removals_brute_force(&self, policy: &SafetyPolicy) -> Option<usize> {
+        (0..=policy.max_removals.min(self.levels.len())).find(|removals| {
+            (0..self.levels.len())
+                .combinations(*removals)
+                .any(|indices_to_remove| {
+                    let new_levels = self
+                        .levels
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| !indices_to_remove.contains(i))
+                        .map(|(_, l)| *l)
+                        .collect::<Vec<_>>();
+                    Self::is_safe_impl(&new_levels, policy)
+                })
+        })
     }
 }
 
@@ -107,7 +441,7 @@ impl FromStr for Report {
 fn test_sample_reports_safety() {
     fn do_test(input: &str, should_be_safe: bool) {
         let report = Report::from_str(input).expect("Should parse");
-        assert_eq!(report.is_safe(), should_be_safe);
+        assert_eq!(report.is_safe(&SafetyPolicy::default()), should_be_safe);
     }
     do_test("7 6 4 2 1", true);
     do_test("1 2 7 8 9", false);
@@ -121,7 +455,7 @@ fn test_sample_reports_safety() {
 fn test_sample_reports_dampened_safety() {
     fn do_test(input: &str, should_be_safe: bool) {
         let report = Report::from_str(input).expect("Should parse");
-        assert_eq!(report.is_safe_when_dampened(), should_be_safe);
+        assert_eq!(report.is_safe(&SafetyPolicy::dampened()), should_be_safe);
     }
     do_test("7 6 4 2 1", true);
     do_test("1 2 7 8 9", false);
@@ -130,3 +464,215 @@ fn test_sample_reports_dampened_safety() {
     do_test("8 6 4 4 1", true);
     do_test("1 3 6 7 9", true);
 }
+
+#[test]
+fn test_custom_policies() {
+    fn is_safe(input: &str, policy: &SafetyPolicy) -> bool {
+        Report::from_str(input)
+            .expect("Should parse")
+            .is_safe(policy)
+    }
+
+    let wide_steps = SafetyPolicy {
+        steps: 1..=5,
+        ..Default::default()
+    };
+    assert!(is_safe("1 2 7 8 9", &wide_steps));
+    assert!(!is_safe("9 7 6 2 1", &SafetyPolicy::default()));
+    assert!(is_safe("9 7 6 2 1", &wide_steps));
+
+    let equal = SafetyPolicy {
+        allow_equal: true,
+        ..Default::default()
+    };
+    assert!(is_safe("8 6 4 4 1", &equal));
+    assert!(is_safe("5 5 5", &equal));
+    assert!(!is_safe("1 3 2 4 5", &equal));
+
+    let ascending = SafetyPolicy {
+        direction: Some(LevelDirection::Ascending),
+        ..Default::default()
+    };
+    assert!(is_safe("1 3 6 7 9", &ascending));
+    assert!(!is_safe("7 6 4 2 1", &ascending));
+
+    let twice_dampened = SafetyPolicy {
+        max_removals: 2,
+        ..Default::default()
+    };
+    assert!(!is_safe("1 9 2 8 3", &SafetyPolicy::dampened()));
+    assert!(is_safe("1 9 2 8 3", &twice_dampened));
+}
+
+#[test]
+fn test_policy_from_options() {
+    let options = |s: &str| {
+        s.split_whitespace()
+            .map(|o| o.to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(SafetyPolicy::from_options(&options("")).unwrap(), None);
+    assert_eq!(
+        SafetyPolicy::from_options(&options(
+            "--max-step 5 --direction descending --max-removals 2"
+        ))
+        .unwrap(),
+        Some(SafetyPolicy {
+            steps: 1..=5,
+            allow_equal: false,
+            direction: Some(LevelDirection::Descending),
+            max_removals: 2,
+        })
+    );
+    assert!(SafetyPolicy::from_options(&options("--min-step 4")).is_err());
+    assert!(SafetyPolicy::from_options(&options("--direction sideways")).is_err());
+}
+
+#[test]
+fn test_levels_to_remove() {
+    let report = Report::from_str("1 3 2 4 5").unwrap();
+    assert_eq!(report.levels_to_remove(&SafetyPolicy::default()), None);
+    assert_eq!(
+        report.levels_to_remove(&SafetyPolicy::dampened()),
+        Some(vec![2])
+    );
+
+    let report = Report::from_str("7 6 4 2 1").unwrap();
+    assert_eq!(
+        report.levels_to_remove(&SafetyPolicy::default()),
+        Some(vec![])
+    );
+
+    let report = Report::from_str("1 9 2 8 3").unwrap();
+    let twice_dampened = SafetyPolicy {
+        max_removals: 2,
+        ..Default::default()
+    };
+    assert_eq!(report.levels_to_remove(&twice_dampened), Some(vec![1, 3]));
+}
+
+#[cfg(test)]
+struct XorShift(u64);
+
+#[cfg(test)]
+impl XorShift {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+}
+
+#[test]
+fn test_levels_to_remove_matches_brute_force() {
+    let mut rng = XorShift(0x2024_1202);
+
+    for _ in 0..5000 {
+        let levels = (0..rng.below(10))
+            .map(|_| rng.below(12) as u32)
+            .collect::<Vec<_>>();
+        let min_step = rng.below(3) as u32;
+        let policy = SafetyPolicy {
+            steps: min_step..=min_step + rng.below(4) as u32,
+            allow_equal: rng.below(2) == 0,
+            direction: match rng.below(3) {
+                0 => None,
+                1 => Some(LevelDirection::Ascending),
+                _ => Some(LevelDirection::Descending),
+            },
+            max_removals: rng.below(4) as usize,
+        };
+        let report = Report { levels };
+
+        let removed = report.levels_to_remove(&policy);
+        assert_eq!(
+            removed.as_ref().map(|r| r.len()),
+            report.fewest_removals_brute_force(&policy),
+            "{:?} {:?}",
+            report.levels,
+            policy
+        );
+
+        if let Some(removed) = removed {
+            let remaining = report
+                .levels
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !removed.contains(i))
+                .map(|(_, l)| *l)
+                .collect::<Vec<_>>();
+            assert!(Report::is_safe_impl(&remaining, &policy));
+        }
+    }
+}
+
+#[test]
+fn test_explain() {
+    fn explain(input: &str, policy: &SafetyPolicy) -> Option<Explanation> {
+        Report::from_str(input)
+            .expect("Should parse")
+            .explain(policy)
+    }
+    let dampened = SafetyPolicy::dampened();
+
+    assert_eq!(explain("7 6 4 2 1", &dampened), None);
+    assert_eq!(
+        explain("1 2 7 8 9", &dampened),
+        Some(Explanation {
+            reasons: vec![UnsafeReason::StepTooLarge { index: 1, step: 5 }],
+            fix: None
+        })
+    );
+    assert_eq!(
+        explain("1 3 2 4 5", &dampened),
+        Some(Explanation {
+            reasons: vec![UnsafeReason::DirectionChange { index: 1 }],
+            fix: Some(vec![2])
+        })
+    );
+    assert_eq!(
+        explain("8 6 4 4 1", &dampened),
+        Some(Explanation {
+            reasons: vec![UnsafeReason::DuplicateLevel { index: 2 }],
+            fix: Some(vec![3])
+        })
+    );
+
+    let big_steps = SafetyPolicy {
+        steps: 2..=3,
+        direction: Some(LevelDirection::Descending),
+        ..Default::default()
+    };
+    assert_eq!(
+        explain("1 3 6 7", &big_steps),
+        Some(Explanation {
+            reasons: vec![
+                UnsafeReason::DirectionChange { index: 0 },
+                UnsafeReason::DirectionChange { index: 1 },
+                UnsafeReason::DirectionChange { index: 2 }
+            ],
+            fix: None
+        })
+    );
+    assert_eq!(
+        explain("9 7 6 3", &big_steps),
+        Some(Explanation {
+            reasons: vec![UnsafeReason::StepTooSmall { index: 1, step: 1 }],
+            fix: None
+        })
+    );
+}
+
+#[test]
+fn test_explanation_numbers_levels_from_1() {
+    let explanation = Report::from_str("20 21 24 25 27 29 27")
+        .expect("Should parse")
+        .explain(&SafetyPolicy::dampened())
+        .expect("Isn't safe");
+    assert_eq!(
+        explanation.to_string(),
+        "  levels 6 and 7 change direction\n  safe after removing level 7\n"
+    );
+}
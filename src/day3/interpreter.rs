@@ -0,0 +1,279 @@
+use std::error::Error;
+
+use super::lexer::{OperandSyntax, Token};
+
+/// The state the instructions work on
+#[derive(Debug)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: u64,
+}
+
+type Semantics = fn(&mut Machine, &[i64]) -> Result<(), Box<dyn Error>>;
+
+/// How an instruction is written in the corrupted memory. Numbers are written the way the set's
+/// `OperandSyntax` says.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Form {
+    /// `name(a,b,...)` with `arity` numbers between the brackets
+    Call,
+    /// Exactly the text given, with a number wherever there's a `#`, like `[#]^2`
+    Template(&'static str),
+}
+
+/// An instruction the interpreter understands
+#[derive(Clone, Copy)]
+pub struct Instruction {
+    pub name: &'static str,
+    /// How many numbers it takes
+    pub arity: usize,
+    pub form: Form,
+    /// Skipped while the machine is disabled
+    pub conditional: bool,
+    pub semantics: Semantics,
+}
+
+/// Adds the result of an instruction to the total, which can't go below zero
+pub fn add_to_total(machine: &mut Machine, value: Option<i64>) -> Result<(), Box<dyn Error>> {
+    let value = value.ok_or("Result overflowed")?;
+    machine.total = machine.total.checked_add_signed(value).ok_or_else(|| {
+        format!(
+            "Adding {} to the total of {} doesn't fit",
+            value, machine.total
+        )
+    })?;
+    Ok(())
+}
+
+/// Every instruction there is, to choose from
+const CATALOGUE: &[Instruction] = &[
+    Instruction {
+        name: "mul",
+        arity: 2,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, ops| add_to_total(m, ops[0].checked_mul(ops[1])),
+    },
+    Instruction {
+        name: "do",
+        arity: 0,
+        form: Form::Call,
+        conditional: false,
+        semantics: |m, _| {
+            m.enabled = true;
+            Ok(())
+        },
+    },
+    Instruction {
+        name: "don't",
+        arity: 0,
+        form: Form::Call,
+        conditional: false,
+        semantics: |m, _| {
+            m.enabled = false;
+            Ok(())
+        },
+    },
+    Instruction {
+        name: "add",
+        arity: 2,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, ops| add_to_total(m, ops[0].checked_add(ops[1])),
+    },
+    Instruction {
+        name: "sub",
+        arity: 2,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, ops| add_to_total(m, ops[0].checked_sub(ops[1])),
+    },
+    Instruction {
+        name: "div",
+        arity: 2,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, ops| {
+            if ops[1] == 0 {
+                return Err(format!("div({},{}) divides by zero", ops[0], ops[1]).into());
+            }
+            add_to_total(m, ops[0].checked_div(ops[1]))
+        },
+    },
+    Instruction {
+        name: "madd",
+        arity: 3,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, ops| {
+            add_to_total(
+                m,
+                ops[0]
+                    .checked_mul(ops[1])
+                    .and_then(|p| p.checked_add(ops[2])),
+            )
+        },
+    },
+    Instruction {
+        name: "reset",
+        arity: 0,
+        form: Form::Call,
+        conditional: true,
+        semantics: |m, _| {
+            m.total = 0;
+            Ok(())
+        },
+    },
+    Instruction {
+        name: "inc",
+        arity: 1,
+        form: Form::Template("+=#"),
+        conditional: true,
+        semantics: |m, ops| add_to_total(m, Some(ops[0])),
+    },
+];
+
+/// The instructions a particular interpreter pays attention to; anything else in the memory is ignored
+pub struct InstructionSet {
+    pub instructions: Vec<Instruction>,
+    /// How the numbers between the brackets are written
+    pub syntax: OperandSyntax,
+}
+
+impl InstructionSet {
+    /// Part 1 only knows about `mul`
+    pub fn part1() -> Self {
+        Self::from_names(&["mul"]).expect("mul is in the catalogue")
+    }
+
+    /// Part 2 also knows `do()` and `don't()`
+    pub fn part2() -> Self {
+        Self::from_names(&["mul", "do", "don't"]).expect("mul, do and don't are in the catalogue")
+    }
+
+    /// The named instructions from the ones built in
+    pub fn from_names(names: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let instructions = names
+            .iter()
+            .map(|name| {
+                CATALOGUE
+                    .iter()
+                    .find(|i| i.name == *name)
+                    .copied()
+                    .ok_or_else(|| format!("There's no instruction called '{}'", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(instructions)
+    }
+
+    /// A set of any instructions at all, built in or not.
+    /// Where two could match at the same place, the one that comes first wins.
+    pub fn new(instructions: Vec<Instruction>) -> Result<Self, Box<dyn Error>> {
+        for instruction in instructions.iter() {
+            if let Form::Template(template) = instruction.form {
+                let numbers = template.matches('#').count();
+                if numbers != instruction.arity {
+                    return Err(format!(
+                        "{} takes {} numbers but '{}' has room for {}",
+                        instruction.name, instruction.arity, template, numbers
+                    )
+                    .into());
+                }
+                if template.is_empty() || template.starts_with('#') {
+                    return Err(format!(
+                        "{}'s template '{}' has to start with some text",
+                        instruction.name, template
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(InstructionSet {
+            instructions,
+            syntax: OperandSyntax::default(),
+        })
+    }
+
+    pub fn with_syntax(self, syntax: OperandSyntax) -> Self {
+        InstructionSet { syntax, ..self }
+    }
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Machine {
+            enabled: true,
+            total: 0,
+        }
+    }
+
+    /// Runs one instruction found by the lexer, which refers to an instruction in the given set.
+    /// Returns whether it ran, which it doesn't if it's conditional and the machine is disabled
+    pub fn execute(&mut self, set: &InstructionSet, token: &Token) -> Result<bool, Box<dyn Error>> {
+        let instruction = &set.instructions[token.instruction];
+        if !self.enabled && instruction.conditional {
+            return Ok(false);
+        }
+        (instruction.semantics)(self, &token.operands).map_err(|e| {
+            format!(
+                "{} at offset {} failed: {}",
+                instruction.name, token.offset, e
+            )
+        })?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+fn run_with(names: &[&str], input: &str) -> Result<u64, Box<dyn Error>> {
+    let set = InstructionSet::from_names(names)?;
+    super::evaluate(input.as_bytes(), &set)
+}
+
+#[test]
+fn test_extra_instructions() {
+    let names = [
+        "mul", "add", "sub", "div", "madd", "reset", "inc", "do", "don't",
+    ];
+    assert_eq!(
+        run_with(&names, "add(2,3)sub(9,4)div(9,2)madd(2,3,4)").unwrap(),
+        5 + 5 + 4 + 10
+    );
+    assert_eq!(run_with(&names, "mul(2,3)reset()add(1,1)").unwrap(), 2);
+    assert_eq!(run_with(&names, "+=5mul(1,1)+=x+= 2+=-3").unwrap(), 6);
+    // like mul(1234,5), a number with too many digits doesn't count, even with nothing after it
+    assert_eq!(run_with(&names, "+=1234+=12").unwrap(), 12);
+    assert_eq!(
+        run_with(&names, "don't()add(1,1)reset()do()mul(2,2)").unwrap(),
+        4
+    );
+    // only the instructions asked for are recognised
+    assert_eq!(run_with(&["add"], "mul(2,3)add(1,1)").unwrap(), 2);
+    // arity has to match exactly
+    assert_eq!(run_with(&names, "add(1)add(1,2,3)madd(1,2,)").unwrap(), 0);
+    // though like the mul in do_not_mul(5,5), a shorter instruction can hide inside a broken one
+    assert_eq!(run_with(&names, "madd(1,2)").unwrap(), 3);
+
+    // the total can't go below zero
+    assert!(run_with(&names, "sub(1,2)").is_err());
+    assert_eq!(run_with(&names, "add(1,2)sub(1,2)").unwrap(), 2);
+    assert!(run_with(&names, "div(1,0)").is_err());
+    assert!(InstructionSet::from_names(&["jmp"]).is_err());
+}
+
+#[test]
+fn test_total_overflow() {
+    let set = InstructionSet::part1();
+    let mut machine = Machine {
+        enabled: true,
+        total: u64::MAX - 5,
+    };
+    let mul = |a, b| Token {
+        instruction: 0,
+        operands: vec![a, b],
+        offset: 0,
+        len: 8,
+    };
+    assert!(machine.execute(&set, &mul(2, 2)).is_ok());
+    assert!(machine.execute(&set, &mul(1, 2)).is_err());
+}
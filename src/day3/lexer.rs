@@ -0,0 +1,488 @@
+use std::{error::Error, ops::Range};
+
+use crate::options::{has_flag, parsed_value_of};
+
+use super::interpreter::{Form, Instruction, InstructionSet};
+
+/// How the numbers in an instruction are allowed to be written. The default is the puzzle's, one to three
+/// decimal digits with nothing else around them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperandSyntax {
+    pub min_digits: usize,
+    pub max_digits: usize,
+    /// Spaces, tabs and newlines are allowed either side of each number
+    pub whitespace: bool,
+    /// Numbers can start with a minus sign
+    pub signed: bool,
+    /// 2 to 36, with letters of either case for the digits past 9
+    pub radix: u32,
+}
+
+impl Default for OperandSyntax {
+    fn default() -> Self {
+        OperandSyntax {
+            min_digits: 1,
+            max_digits: 3,
+            whitespace: false,
+            signed: false,
+            radix: 10,
+        }
+    }
+}
+
+impl OperandSyntax {
+    /// The syntax described by the command line options, starting from the default
+    pub fn from_options(options: &[String]) -> Result<Self, Box<dyn Error>> {
+        let default = OperandSyntax::default();
+        let syntax = OperandSyntax {
+            min_digits: parsed_value_of(options, "--min-digits")?.unwrap_or(default.min_digits),
+            max_digits: parsed_value_of(options, "--max-digits")?.unwrap_or(default.max_digits),
+            whitespace: has_flag(options, "--whitespace"),
+            signed: has_flag(options, "--signed"),
+            radix: parsed_value_of(options, "--radix")?.unwrap_or(default.radix),
+        };
+
+        if syntax.min_digits == 0 || syntax.min_digits > syntax.max_digits {
+            return Err(format!(
+                "Numbers can't have between {} and {} digits",
+                syntax.min_digits, syntax.max_digits
+            )
+            .into());
+        }
+        if !(2..=36).contains(&syntax.radix) {
+            return Err(format!("There's no such thing as base {}", syntax.radix).into());
+        }
+
+        Ok(syntax)
+    }
+}
+
+/// An instruction from the set being looked for, its operands, and where it is in the input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    /// Index into the instruction set
+    pub instruction: usize,
+    pub operands: Vec<i64>,
+    /// The byte offset where it starts
+    pub offset: usize,
+    /// How many bytes of the input it takes up
+    pub len: usize,
+}
+
+impl Token {
+    /// The bytes of the input it takes up
+    pub fn span(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// What was found when trying to read an instruction
+enum Scan {
+    Found(usize, Vec<i64>),
+    Nothing,
+    /// The input ran out partway through what might still turn out to be an instruction
+    Incomplete,
+}
+
+/// Picks the instructions out of corrupted memory, skipping over everything else.
+/// Anything that doesn't turn out to be a complete instruction is skipped one byte at a time, so an
+/// instruction starting inside a broken one is still found.
+/// It can work on one chunk of a bigger input at a time, stopping at anything the chunk cuts off.
+pub struct Lexer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Where the input starts in the whole thing, added to the offsets handed out
+    base_offset: usize,
+    /// Whether there's more input to come after this
+    more_to_come: bool,
+    /// Set whenever reading something runs into the end of the input
+    hit_end: bool,
+    instructions: &'a InstructionSet,
+    near_misses: Option<Vec<Range<usize>>>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str, instructions: &'a InstructionSet) -> Self {
+        Lexer {
+            input: input.as_bytes(),
+            pos: 0,
+            base_offset: 0,
+            more_to_come: false,
+            hit_end: false,
+            instructions,
+            near_misses: None,
+        }
+    }
+
+    /// A lexer for part of a bigger input, starting `base_offset` bytes into it.
+    /// Once it stops, `consumed` says how much of the chunk it's finished with; the rest needs to be passed
+    /// in again at the start of the next chunk.
+    pub fn chunk(
+        input: &'a [u8],
+        base_offset: usize,
+        more_to_come: bool,
+        instructions: &'a InstructionSet,
+    ) -> Self {
+        Lexer {
+            input,
+            pos: 0,
+            base_offset,
+            more_to_come,
+            hit_end: false,
+            instructions,
+            near_misses: None,
+        }
+    }
+
+    /// Also keep track of near misses, like `mul(32,64]`: an instruction name and its opening bracket that
+    /// don't go on to make a whole instruction
+    pub fn recording_near_misses(mut self) -> Self {
+        self.near_misses = Some(Vec::new());
+        self
+    }
+
+    /// The byte ranges of the near misses found so far, each running up to and including the byte where the
+    /// instruction went wrong. Empty unless they're being recorded.
+    pub fn near_misses(&self) -> &[Range<usize>] {
+        self.near_misses.as_deref().unwrap_or_default()
+    }
+
+    /// How many bytes of the input have been dealt with
+    pub fn consumed(&self) -> usize {
+        self.pos
+    }
+
+    /// Consumes `literal` if it comes next
+    fn literal(&mut self, literal: &str) -> bool {
+        let rest = &self.input[self.pos..];
+        if rest.starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            true
+        } else {
+            if literal.as_bytes().starts_with(rest) {
+                self.hit_end = true;
+            }
+            false
+        }
+    }
+
+    /// Skips any whitespace, if the syntax allows it
+    fn whitespace(&mut self) {
+        if !self.instructions.syntax.whitespace {
+            return;
+        }
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+        if self.pos == self.input.len() {
+            self.hit_end = true;
+        }
+    }
+
+    /// Consumes a number written the way the syntax says if one comes next.
+    /// Numbers too big to fit don't count.
+    fn number(&mut self) -> Option<i64> {
+        let syntax = self.instructions.syntax;
+        self.whitespace();
+        let negative = syntax.signed && self.literal("-");
+
+        let digits = self.input[self.pos..]
+            .iter()
+            .take(syntax.max_digits)
+            .take_while(|b| (**b as char).is_digit(syntax.radix))
+            .count();
+        if digits < syntax.max_digits && self.pos + digits == self.input.len() {
+            self.hit_end = true;
+        }
+        if digits < syntax.min_digits {
+            return None;
+        }
+
+        let mut number: i64 = 0;
+        for b in self.input[self.pos..self.pos + digits].iter() {
+            let digit = (*b as char)
+                .to_digit(syntax.radix)
+                .expect("Already checked it's a digit");
+            number = number
+                .checked_mul(syntax.radix as i64)?
+                .checked_add(digit as i64)?;
+        }
+        self.pos += digits;
+        self.whitespace();
+
+        Some(if negative { -number } else { number })
+    }
+
+    /// Consumes `name(a,b,...)` with the right number of operands for the instruction if it comes next
+    fn call(&mut self, instruction: &Instruction) -> Option<Vec<i64>> {
+        if !self.literal(instruction.name) || !self.literal("(") {
+            return None;
+        }
+        let mut operands = Vec::with_capacity(instruction.arity);
+        for i in 0..instruction.arity {
+            if i > 0 && !self.literal(",") {
+                return None;
+            }
+            operands.push(self.number()?);
+        }
+        if instruction.arity == 0 {
+            self.whitespace();
+        }
+        if !self.literal(")") {
+            return None;
+        }
+        Some(operands)
+    }
+
+    /// Consumes the template's text with a number wherever it has a `#` if it comes next
+    fn template(&mut self, template: &str) -> Option<Vec<i64>> {
+        let mut operands = Vec::new();
+        for (i, text) in template.split('#').enumerate() {
+            if i > 0 {
+                operands.push(self.number()?);
+            }
+            if !self.literal(text) {
+                return None;
+            }
+        }
+        // a number at the very end has nothing after it to stop it, so it mustn't run on into more digits,
+        // including ones that might be in the next chunk
+        if template.ends_with('#') {
+            match self.input.get(self.pos) {
+                Some(b) if (*b as char).is_digit(self.instructions.syntax.radix) => return None,
+                Some(_) => {}
+                None => self.hit_end = true,
+            }
+        }
+        Some(operands)
+    }
+
+    /// Consumes the instruction however it's written if it comes next
+    fn read(&mut self, instruction: &Instruction) -> Option<Vec<i64>> {
+        match instruction.form {
+            Form::Call => self.call(instruction),
+            Form::Template(template) => self.template(template),
+        }
+    }
+
+    /// Tries to read any instruction in the set starting at the current position
+    fn instruction(&mut self) -> Scan {
+        let start = self.pos;
+        let mut furthest_miss = None;
+        for (index, instruction) in self.instructions.instructions.iter().enumerate() {
+            self.hit_end = false;
+            let operands = self.read(instruction);
+            if self.hit_end && self.more_to_come {
+                // this one might work out, or turn out to be longer, once the rest of it arrives
+                self.pos = start;
+                return Scan::Incomplete;
+            }
+            if let Some(operands) = operands {
+                return Scan::Found(index, operands);
+            }
+            // a failed read stops just before whatever it didn't like
+            if self.pos >= start + lead_len(instruction) {
+                furthest_miss = furthest_miss.max(Some((self.pos + 1).min(self.input.len())));
+            }
+            self.pos = start;
+        }
+        if let (Some(near_misses), Some(end)) = (self.near_misses.as_mut(), furthest_miss) {
+            near_misses.push(self.base_offset + start..self.base_offset + end);
+        }
+        Scan::Nothing
+    }
+}
+
+/// How much of an instruction has to be there for it to be a near miss if the rest isn't: the name and
+/// opening bracket of a call, or the text before a template's first number
+fn lead_len(instruction: &Instruction) -> usize {
+    match instruction.form {
+        Form::Call => instruction.name.len() + 1,
+        Form::Template(template) => template.split('#').next().unwrap_or_default().len(),
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.input.len() {
+            let offset = self.pos;
+            match self.instruction() {
+                Scan::Found(instruction, operands) => {
+                    return Some(Token {
+                        instruction,
+                        operands,
+                        offset: self.base_offset + offset,
+                        len: self.pos - offset,
+                    })
+                }
+                Scan::Nothing => self.pos = offset + 1,
+                Scan::Incomplete => return None,
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+fn calls(input: &str) -> Vec<(usize, Vec<i64>)> {
+    Lexer::new(input, &InstructionSet::part2())
+        .map(|t| (t.instruction, t.operands))
+        .collect()
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(calls("mul(1,2)"), vec![(0, vec![1, 2])]);
+    assert_eq!(calls("mul(100,2)"), vec![(0, vec![100, 2])]);
+    assert_eq!(calls("mul(333,200)"), vec![(0, vec![333, 200])]);
+    assert_eq!(calls("mul(1234,5)mul(12,3456)"), vec![]);
+    assert_eq!(calls("mul( 1,2)mul(1,2 )mul(-1,2)"), vec![]);
+}
+
+#[test]
+fn test_instruction_inside_broken_one() {
+    assert_eq!(
+        Lexer::new("mul(4,mul(5,6)don't(do()", &InstructionSet::part2()).collect::<Vec<_>>(),
+        vec![
+            Token {
+                instruction: 0,
+                operands: vec![5, 6],
+                offset: 6,
+                len: 8
+            },
+            Token {
+                instruction: 1,
+                operands: vec![],
+                offset: 20,
+                len: 4
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_near_misses() {
+    let set = InstructionSet::part2();
+    let mut lexer =
+        Lexer::new("mul(32,64]do_not_mul(5,5)mul(4*don't(x", &set).recording_near_misses();
+    assert_eq!(lexer.by_ref().count(), 1);
+    assert_eq!(lexer.near_misses(), &[0..10, 25..31, 31..38]);
+}
+
+#[test]
+fn test_chunk_stops_at_cut_off_instruction() {
+    let set = InstructionSet::part2();
+    let mut lexer = Lexer::chunk(b"xmul(2,4)&mul(3,", 100, true, &set);
+    assert_eq!(
+        lexer.by_ref().collect::<Vec<_>>(),
+        vec![Token {
+            instruction: 0,
+            operands: vec![2, 4],
+            offset: 101,
+            len: 8
+        }]
+    );
+    assert_eq!(lexer.consumed(), 10);
+
+    // a number at the very end might have more digits to come
+    let mut lexer = Lexer::chunk(b"mul(3,7", 0, true, &set);
+    assert_eq!(lexer.by_ref().count(), 0);
+    assert_eq!(lexer.consumed(), 0);
+
+    // but not if it's the end of everything
+    let mut lexer = Lexer::chunk(b"mul(3,7", 0, false, &set);
+    assert_eq!(lexer.by_ref().count(), 0);
+    assert_eq!(lexer.consumed(), 7);
+}
+
+#[test]
+fn test_operand_syntax_matrix() {
+    let default = OperandSyntax::default();
+    let configs = [
+        default,
+        OperandSyntax {
+            max_digits: 4,
+            ..default
+        },
+        OperandSyntax {
+            whitespace: true,
+            ..default
+        },
+        OperandSyntax {
+            signed: true,
+            ..default
+        },
+        OperandSyntax {
+            radix: 16,
+            ..default
+        },
+        OperandSyntax {
+            min_digits: 3,
+            ..default
+        },
+    ];
+
+    // what each config makes of each input, in the same order as the configs:
+    // default, up to four digits, whitespace, signed, hexadecimal, exactly three digits
+    let none = None;
+    #[rustfmt::skip]
+    let cases = [
+        ("mul(1,2)",     [Some((1, 2)), Some((1, 2)),    Some((1, 2)), Some((1, 2)),  Some((1, 2)),   none]),
+        ("mul(001,002)", [Some((1, 2)), Some((1, 2)),    Some((1, 2)), Some((1, 2)),  Some((1, 2)),   Some((1, 2))]),
+        ("mul(1234,5)",  [none,         Some((1234, 5)), none,         none,          none,           none]),
+        ("mul( 1,2)",    [none,         none,            Some((1, 2)), none,          none,           none]),
+        ("mul(1 , 2 )",  [none,         none,            Some((1, 2)), none,          none,           none]),
+        ("mul(1,\n2)",   [none,         none,            Some((1, 2)), none,          none,           none]),
+        ("mul(-1,2)",    [none,         none,            none,         Some((-1, 2)), none,           none]),
+        ("mul(1,-2)",    [none,         none,            none,         Some((1, -2)), none,           none]),
+        ("mul(+1,2)",    [none,         none,            none,         none,          none,           none]),
+        ("mul(ff,10)",   [none,         none,            none,         none,          Some((255, 16)), none]),
+        ("mul(1A,2)",    [none,         none,            none,         none,          Some((26, 2)),  none]),
+        ("mul(0x1,2)",   [none,         none,            none,         none,          none,           none]),
+        ("mul(,2)",      [none,         none,            none,         none,          none,           none]),
+        ("mul(1,2",      [none,         none,            none,         none,          none,           none]),
+        ("mul[1,2]",     [none,         none,            none,         none,          none,           none]),
+    ];
+
+    for (input, expected) in cases {
+        for (syntax, expected) in configs.iter().zip(expected) {
+            let set = InstructionSet::part1().with_syntax(*syntax);
+            let found = Lexer::new(input, &set)
+                .map(|t| (t.operands[0], t.operands[1]))
+                .next();
+            assert_eq!(found, expected, "{:?} with {:?}", input, syntax);
+        }
+    }
+}
+
+#[test]
+fn test_operand_syntax_from_options() {
+    let options = |s: &str| {
+        s.split_whitespace()
+            .map(|o| o.to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        OperandSyntax::from_options(&options("")).unwrap(),
+        OperandSyntax::default()
+    );
+    assert_eq!(
+        OperandSyntax::from_options(&options("--max-digits 5 --signed --radix 8")).unwrap(),
+        OperandSyntax {
+            min_digits: 1,
+            max_digits: 5,
+            whitespace: false,
+            signed: true,
+            radix: 8
+        }
+    );
+    assert!(OperandSyntax::from_options(&options("--min-digits 4")).is_err());
+    assert!(OperandSyntax::from_options(&options("--radix 37")).is_err());
+}
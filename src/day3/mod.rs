@@ -0,0 +1,166 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::Read,
+};
+
+use interpreter::InstructionSet;
+use lexer::OperandSyntax;
+use stream::evaluate_stream;
+use trace::Trace;
+
+use crate::options::{has_flag, value_of};
+
+mod interpreter;
+mod lexer;
+mod stream;
+mod trace;
+
+/// Options:
+///   --instructions a,b,...  run with only the named instructions instead of the two puzzle parts,
+///                           from mul, do, don't, add, sub, div, madd, reset and inc (written +=N)
+///   --trace                 print the input with the instructions highlighted and a table of what each one did,
+///                           using the part 2 instructions unless --instructions says otherwise, and the file
+///                           given by --input if there is one
+///   --input FILE            read the corrupted memory from a file, a chunk at a time, instead of the puzzle input
+///   --min-digits N          fewest digits a number can have (default 1)
+///   --max-digits N          most digits a number can have (default 3)
+///   --whitespace            allow whitespace either side of the numbers
+///   --signed                allow negative numbers
+///   --radix N               the base the numbers are written in (default 10)
+pub fn run(options: &[String]) -> Result<(), Box<dyn Error>> {
+    let input = include_str!("../inputs/day3.txt");
+    let syntax = OperandSyntax::from_options(options)?;
+    let path = value_of(options, "--input")?;
+    let source = || -> Result<Box<dyn Read>, Box<dyn Error>> {
+        match path {
+            Some(path) => Ok(Box::new(File::open(path)?)),
+            None => Ok(Box::new(input.as_bytes())),
+        }
+    };
+
+    let set = match value_of(options, "--instructions")? {
+        Some(names) => Some(
+            InstructionSet::from_names(&names.split(',').collect::<Vec<_>>())?.with_syntax(syntax),
+        ),
+        None => None,
+    };
+
+    if has_flag(options, "--trace") {
+        let set = set.unwrap_or_else(|| InstructionSet::part2().with_syntax(syntax));
+        // the trace needs all of the input at once to highlight it, so a file is read in whole
+        let input = match path {
+            Some(path) => {
+                fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?
+            }
+            None => input.to_owned(),
+        };
+        let trace = Trace::new(&input, &set)?;
+        println!("{}", trace.highlight(&input));
+        print!("{}", trace.table(&input));
+        return Ok(());
+    }
+
+    if let Some(set) = set {
+        println!("The sum is {}", evaluate(source()?, &set)?);
+        return Ok(());
+    }
+
+    let part1 = part1(source()?, syntax)?;
+    println!("Part 1: The sum is {}", part1);
+
+    let part2 = part2(source()?, syntax)?;
+    println!("Part 2: The sum is {}", part2);
+
+    Ok(())
+}
+
+const CHUNK_SIZE: usize = 8192;
+
+fn evaluate(input: impl Read, set: &InstructionSet) -> Result<u64, Box<dyn Error>> {
+    Ok(evaluate_stream(input, set, CHUNK_SIZE)?.total)
+}
+
+fn part1(input: impl Read, syntax: OperandSyntax) -> Result<u64, Box<dyn Error>> {
+    evaluate(input, &InstructionSet::part1().with_syntax(syntax))
+}
+
+fn part2(input: impl Read, syntax: OperandSyntax) -> Result<u64, Box<dyn Error>> {
+    evaluate(input, &InstructionSet::part2().with_syntax(syntax))
+}
+
+#[cfg(test)]
+const TEST_INPUT: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+#[cfg(test)]
+const TEST_INPUT_PART2: &str =
+    "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+#[test]
+fn test_part1_sample_extract() {
+    let set = InstructionSet::part1();
+    assert_eq!(
+        lexer::Lexer::new(TEST_INPUT, &set)
+            .map(|t| (t.offset, t.operands))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, vec![2, 4]),
+            (29, vec![5, 5]),
+            (53, vec![11, 8]),
+            (62, vec![8, 5])
+        ]
+    );
+}
+
+#[test]
+fn test_part1() {
+    assert_eq!(
+        part1(TEST_INPUT.as_bytes(), OperandSyntax::default()).unwrap(),
+        161
+    );
+}
+
+#[test]
+fn test_part2() {
+    assert_eq!(
+        part2(TEST_INPUT_PART2.as_bytes(), OperandSyntax::default()).unwrap(),
+        48
+    );
+}
+
+#[test]
+fn test_declared_instructions() {
+    use interpreter::{add_to_total, Form, Instruction};
+
+    let square = Instruction {
+        name: "square",
+        arity: 1,
+        form: Form::Template("[#]^2"),
+        conditional: true,
+        semantics: |m, ops| add_to_total(m, ops[0].checked_mul(ops[0])),
+    };
+    let max = Instruction {
+        name: "max",
+        arity: 2,
+        form: Form::Call,
+        conditional: false,
+        semantics: |m, ops| add_to_total(m, Some(ops[0].max(ops[1]))),
+    };
+    let mut instructions = vec![square, max];
+    instructions.extend(InstructionSet::part2().instructions);
+    let set = InstructionSet::new(instructions).unwrap();
+
+    assert_eq!(
+        evaluate(
+            "[3]^2max(4,7)don't()[5]^2max(1,2)[6]^3do()mul(2,2)".as_bytes(),
+            &set
+        )
+        .unwrap(),
+        9 + 7 + 2 + 4
+    );
+
+    let mismatched = Instruction {
+        form: Form::Template("[#,#]^2"),
+        ..square
+    };
+    assert!(InstructionSet::new(vec![mismatched]).is_err());
+}
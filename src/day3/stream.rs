@@ -0,0 +1,120 @@
+use std::{
+    error::Error,
+    io::{ErrorKind, Read},
+};
+
+use super::{
+    interpreter::{InstructionSet, Machine},
+    lexer::Lexer,
+};
+
+/// Runs the instructions from any source, reading it `chunk_size` bytes at a time so it never has to be all
+/// in memory at once. Whatever's left at the end of a chunk that might be the start of an instruction is
+/// kept and tried again with the next chunk, and the machine carries on from one chunk to the next.
+pub fn evaluate_stream(
+    mut input: impl Read,
+    set: &InstructionSet,
+    chunk_size: usize,
+) -> Result<Machine, Box<dyn Error>> {
+    let mut machine = Machine::new();
+    let mut buffer = Vec::with_capacity(chunk_size * 2);
+    let mut chunk = vec![0; chunk_size.max(1)];
+    let mut buffer_offset = 0;
+
+    loop {
+        let read = match input.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let more_to_come = read > 0;
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let mut lexer = Lexer::chunk(&buffer, buffer_offset, more_to_come, set);
+        for token in lexer.by_ref() {
+            machine.execute(set, &token)?;
+        }
+
+        let consumed = lexer.consumed();
+        buffer.drain(..consumed);
+        buffer_offset += consumed;
+
+        if !more_to_come {
+            return Ok(machine);
+        }
+    }
+}
+
+/// Hands out its data a few bytes at a time, in differently sized pieces
+#[cfg(test)]
+struct Dribble<'a> {
+    data: &'a [u8],
+    reads: usize,
+}
+
+#[cfg(test)]
+impl Read for Dribble<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        let n = (self.reads % 4 + 1).min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_every_chunk_size() {
+    let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    for chunk_size in 1..=input.len() + 1 {
+        let part1 = evaluate_stream(input.as_bytes(), &InstructionSet::part1(), chunk_size);
+        let part2 = evaluate_stream(input.as_bytes(), &InstructionSet::part2(), chunk_size);
+        assert_eq!(part1.unwrap().total, 161, "chunk size {}", chunk_size);
+        assert_eq!(part2.unwrap().total, 48, "chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn test_uneven_reads() {
+    let input = "mul(123,456)don't()mul(1,1)do()mul(999,999)mul(4,mul(5,6)";
+    let reader = Dribble {
+        data: input.as_bytes(),
+        reads: 0,
+    };
+    let machine = evaluate_stream(reader, &InstructionSet::part2(), 64).unwrap();
+    assert_eq!(machine.total, 123 * 456 + 999 * 999 + 5 * 6);
+    assert!(machine.enabled);
+}
+
+#[test]
+fn test_every_chunk_size_with_looser_syntax() {
+    use super::lexer::OperandSyntax;
+
+    let input = "mul(1000,2)mul( -12 ,\n 3 )mul(1 ,-1)don't( )mul(7,7)do()mul(2 , 2)";
+    let set = InstructionSet::part2().with_syntax(OperandSyntax {
+        whitespace: true,
+        signed: true,
+        max_digits: 4,
+        ..Default::default()
+    });
+    for chunk_size in 1..=input.len() + 1 {
+        let machine = evaluate_stream(input.as_bytes(), &set, chunk_size).unwrap();
+        assert_eq!(
+            machine.total,
+            2000 - 36 - 1 + 4,
+            "chunk size {}",
+            chunk_size
+        );
+    }
+}
+
+#[test]
+fn test_every_chunk_size_with_template() {
+    // inc is written +=N, with nothing after the number to say it's finished
+    let input = "+=123mul(2,2)+=1234+=45";
+    let set = InstructionSet::from_names(&["mul", "inc"]).unwrap();
+    for chunk_size in 1..=input.len() + 1 {
+        let machine = evaluate_stream(input.as_bytes(), &set, chunk_size).unwrap();
+        assert_eq!(machine.total, 123 + 4 + 45, "chunk size {}", chunk_size);
+    }
+}
@@ -0,0 +1,238 @@
+use std::{error::Error, fmt::Write, ops::Range};
+
+use super::{
+    interpreter::{InstructionSet, Machine},
+    lexer::{Lexer, Token},
+};
+
+const ENABLED_COLOUR: &str = "\x1b[32m";
+const DISABLED_COLOUR: &str = "\x1b[31m";
+const NEAR_MISS_COLOUR: &str = "\x1b[33m";
+const RESET_COLOUR: &str = "\x1b[0m";
+
+/// One instruction as it was run
+pub struct TraceStep {
+    pub token: Token,
+    /// False if it was skipped because the machine was disabled
+    pub ran: bool,
+    pub total: u64,
+}
+
+/// Everything the interpreter did with the input
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+    pub near_misses: Vec<Range<usize>>,
+}
+
+impl Trace {
+    pub fn new(input: &str, set: &InstructionSet) -> Result<Self, Box<dyn Error>> {
+        let mut lexer = Lexer::new(input, set).recording_near_misses();
+        let mut machine = Machine::new();
+        let mut steps = Vec::new();
+
+        for token in lexer.by_ref() {
+            let ran = machine.execute(set, &token)?;
+            steps.push(TraceStep {
+                token,
+                ran,
+                total: machine.total,
+            });
+        }
+
+        Ok(Trace {
+            steps,
+            near_misses: lexer.near_misses().to_vec(),
+        })
+    }
+
+    /// The input with instructions that ran in green, ones that were skipped in red, and near misses in yellow
+    pub fn highlight(&self, input: &str) -> String {
+        let mut colours = vec![None; input.len()];
+        for miss in self.near_misses.iter() {
+            colours[miss.clone()].fill(Some(NEAR_MISS_COLOUR));
+        }
+        // instructions win over any near misses they overlap
+        for step in self.steps.iter() {
+            let colour = if step.ran {
+                ENABLED_COLOUR
+            } else {
+                DISABLED_COLOUR
+            };
+            colours[step.token.span()].fill(Some(colour));
+        }
+
+        let mut highlighted = String::new();
+        let mut current = None;
+        for (i, c) in input.char_indices() {
+            let colour = colours[i];
+            if colour != current {
+                highlighted.push_str(colour.unwrap_or(RESET_COLOUR));
+                current = colour;
+            }
+            highlighted.push(c);
+        }
+        if current.is_some() {
+            highlighted.push_str(RESET_COLOUR);
+        }
+        highlighted
+    }
+
+    /// A row for each instruction with its offset, how it was written in the input, the operands that were read
+    /// from it, whether it ran and the total afterwards
+    pub fn table(&self, input: &str) -> String {
+        let mut table = format!(
+            "{:>8}  {:<16}  {:<16}  {:<8}  {}\n",
+            "offset", "instruction", "operands", "enabled", "total"
+        );
+        for step in self.steps.iter() {
+            writeln!(
+                table,
+                "{:>8}  {:<16}  {:<16}  {:<8}  {}",
+                step.token.offset,
+                input[step.token.span()].escape_debug().to_string(),
+                step.token
+                    .operands
+                    .iter()
+                    .map(|o| o.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                if step.ran { "yes" } else { "no" },
+                step.total
+            )
+            .expect("Writing to a string can't fail");
+        }
+        table
+    }
+}
+
+#[test]
+fn test_trace() {
+    let set = InstructionSet::part2();
+    let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    let trace = Trace::new(input, &set).unwrap();
+
+    assert_eq!(
+        trace
+            .steps
+            .iter()
+            .map(|s| (s.token.offset, s.ran, s.total))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, true, 8),
+            (20, true, 8),
+            (28, false, 8),
+            (48, false, 8),
+            (59, true, 8),
+            (64, true, 48)
+        ]
+    );
+    assert_eq!(trace.near_misses, vec![37..47]);
+
+    let highlighted = trace.highlight(input);
+    assert!(highlighted.starts_with("x\x1b[32mmul(2,4)\x1b[0m&mul[3,7]"));
+    assert!(highlighted.contains("_\x1b[31mmul(5,5)\x1b[0m+\x1b[33mmul(32,64]\x1b[0m("));
+    assert!(highlighted.ends_with("\x1b[32mmul(8,5)\x1b[0m)"));
+
+    let table = trace.table(input);
+    assert_eq!(table.lines().count(), 7);
+    assert_eq!(
+        table.lines().nth(3),
+        Some("      28  mul(5,5)          5,5               no        8")
+    );
+}
+
+#[test]
+fn test_trace_uses_the_input_text() {
+    let input = "xmul(001,002)y";
+    let trace = Trace::new(input, &InstructionSet::part1()).unwrap();
+    assert_eq!(trace.highlight(input), "x\x1b[32mmul(001,002)\x1b[0my");
+    assert_eq!(
+        trace.table(input).lines().nth(1),
+        Some("       1  mul(001,002)      1,2               yes       2")
+    );
+}
+
+#[test]
+fn test_trace_with_each_operand_syntax() {
+    use super::lexer::OperandSyntax;
+
+    let default = OperandSyntax::default();
+    // each instruction is surrounded by an x and a y so anything highlighted past it would show
+    let cases = [
+        (default, "xmul(001,002)y", "mul(001,002)", "1,2", 2),
+        (
+            OperandSyntax {
+                max_digits: 4,
+                ..default
+            },
+            "xmul(1234,1)y",
+            "mul(1234,1)",
+            "1234,1",
+            1234,
+        ),
+        (
+            OperandSyntax {
+                whitespace: true,
+                ..default
+            },
+            "xmul( 1 ,\n2 )y",
+            "mul( 1 ,\n2 )",
+            "1,2",
+            2,
+        ),
+        (
+            OperandSyntax {
+                signed: true,
+                ..default
+            },
+            "xmul(-1,-02)y",
+            "mul(-1,-02)",
+            "-1,-2",
+            2,
+        ),
+        (
+            OperandSyntax {
+                radix: 16,
+                ..default
+            },
+            "xmul(ff,10)y",
+            "mul(ff,10)",
+            "255,16",
+            255 * 16,
+        ),
+        (
+            OperandSyntax {
+                min_digits: 3,
+                ..default
+            },
+            "xmul(003,004)y",
+            "mul(003,004)",
+            "3,4",
+            12,
+        ),
+    ];
+
+    for (syntax, input, text, operands, total) in cases {
+        let set = InstructionSet::part1().with_syntax(syntax);
+        let trace = Trace::new(input, &set).unwrap();
+        assert_eq!(trace.steps.len(), 1, "{:?} with {:?}", input, syntax);
+        assert_eq!(trace.steps[0].total, total, "{:?} with {:?}", input, syntax);
+        assert_eq!(
+            trace.highlight(input),
+            format!("x{}{}{}y", ENABLED_COLOUR, text, RESET_COLOUR),
+            "{:?} with {:?}",
+            input,
+            syntax
+        );
+        assert!(
+            trace.table(input).contains(&format!(
+                "  {:<16}  {:<16}  yes",
+                text.escape_debug().to_string(),
+                operands
+            )),
+            "{:?} with {:?}",
+            input,
+            syntax
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::error::Error;
+
+use aho_corasick::AhoCorasick;
+use grid::Grid;
+use itertools::Itertools;
+
+use crate::grid_extensions::{Direction, GridExtensions, WordMatch};
+
+use super::WordResults;
+
+/// Every straight line through a grid, rows, columns and both kinds of diagonal, joined up into one string
+/// so they can all be searched at once.
+/// Each line reads in one of the first four directions of `Direction::ALL`, so a word found backwards is
+/// going the opposite way, matching what `find_word` reports.
+pub struct GridLines {
+    haystack: String,
+    /// The byte offset in the haystack of each grid cell's character, in order
+    byte_offsets: Vec<usize>,
+    /// Which cell each character came from, and which way its line reads
+    cells: Vec<((usize, usize), Direction)>,
+}
+
+impl GridLines {
+    pub fn new(grid: &Grid<char>) -> Self {
+        let (rows, cols) = grid.size();
+        let starts = (0..rows)
+            .map(|row| (row, 0, Direction::East))
+            .chain((0..cols).map(|col| (rows - 1, col, Direction::North)))
+            .chain((0..rows).map(|row| (row, 0, Direction::SouthEast)))
+            .chain((1..cols).map(|col| (0, col, Direction::SouthEast)))
+            .chain((0..rows).map(|row| (row, 0, Direction::NorthEast)))
+            .chain((1..cols).map(|col| (rows - 1, col, Direction::NorthEast)));
+
+        let mut lines = GridLines {
+            haystack: String::new(),
+            byte_offsets: Vec::new(),
+            cells: Vec::new(),
+        };
+        for (row, col, direction) in starts {
+            for (pos, c) in grid.line_from(row, col, direction) {
+                lines.byte_offsets.push(lines.haystack.len());
+                lines.cells.push((pos, direction));
+                lines.haystack.push(*c);
+            }
+            // keeps words from running from the end of one line onto the next
+            lines.haystack.push('\n');
+        }
+        lines
+    }
+
+    /// Index of the character starting at or before the byte offset
+    fn char_at(&self, byte_offset: usize) -> usize {
+        self.byte_offsets.partition_point(|b| *b <= byte_offset) - 1
+    }
+
+    /// Looks for all the words at once, forwards and backwards, including where they overlap.
+    /// Gives the same matches as `find_word` for each word, though not necessarily in the same order.
+    pub fn search(&self, words: &[&str]) -> Result<Vec<WordResults>, Box<dyn Error>> {
+        let words = words.iter().unique().collect::<Vec<_>>();
+
+        // each word gets a forwards and a backwards pattern, even if that means looking for the same thing twice,
+        // so the pattern's index says both which word it was and which way it was going.
+        // An empty word would match everywhere without covering any cells, so like find_word it finds nothing.
+        let searched = (0..words.len())
+            .filter(|w| !words[*w].is_empty())
+            .collect::<Vec<_>>();
+        let patterns = searched
+            .iter()
+            .flat_map(|w| [words[*w].to_string(), words[*w].chars().rev().collect()])
+            .collect::<Vec<_>>();
+        let automaton = AhoCorasick::new(&patterns)?;
+
+        let mut results = words
+            .iter()
+            .map(|w| WordResults {
+                word: w.to_string(),
+                matches: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for m in automaton.find_overlapping_iter(&self.haystack) {
+            let pattern = m.pattern().as_usize();
+            let word = searched[pattern / 2];
+            let backwards = pattern % 2 == 1;
+            let (first, direction) = self.cells[self.char_at(m.start())];
+            let (last, _) = self.cells[self.char_at(m.end() - 1)];
+
+            let letters = results[word].word.chars().count();
+            let palindrome = patterns[pattern] == patterns[pattern ^ 1];
+            if (palindrome && backwards) || (letters == 1 && direction != Direction::North) {
+                // the forwards pattern has already found it, and single letters are only counted once,
+                // like find_word does
+                continue;
+            }
+
+            results[word].matches.push(if backwards {
+                WordMatch {
+                    start: last,
+                    direction: direction.opposite(),
+                }
+            } else {
+                WordMatch {
+                    start: first,
+                    direction,
+                }
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+fn sorted(matches: Vec<WordMatch>) -> Vec<((usize, usize), Direction)> {
+    let mut matches = matches
+        .into_iter()
+        .map(|m| (m.start, m.direction))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(start, direction)| (*start, *direction as usize));
+    matches
+}
+
+#[test]
+fn test_same_as_find_word() {
+    let grid = super::load_grid(super::TEST_INPUT);
+    let words = [
+        "XMAS",
+        "MAM",
+        "X",
+        "",
+        "AMXS",
+        "SAMX",
+        "MS",
+        "ZZ",
+        "MMMSXXMASM",
+    ];
+    let lines = GridLines::new(&grid);
+    let results = lines.search(&words).unwrap();
+
+    for (word, found) in words.iter().zip(results) {
+        assert_eq!(found.word, *word);
+        assert_eq!(
+            sorted(found.matches),
+            sorted(grid.find_word(&word.chars().collect::<Vec<_>>())),
+            "{}",
+            word
+        );
+    }
+}
+
+#[test]
+fn test_lines_with_wide_characters() {
+    let grid = super::load_grid("éé€\n€€é");
+    let lines = GridLines::new(&grid);
+    let results = lines.search(&["é€", "€"]).unwrap();
+    assert_eq!(
+        sorted(results[0].matches.clone()),
+        sorted(grid.find_word(&['é', '€']))
+    );
+    assert_eq!(results[1].matches.len(), 3);
+}
+
+#[test]
+fn test_empty_word() {
+    let lines = GridLines::new(&super::load_grid(super::TEST_INPUT));
+    let results = lines.search(&[""]).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].matches.is_empty());
+}
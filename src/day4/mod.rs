@@ -0,0 +1,304 @@
+use std::{error::Error, time::Instant};
+
+use grid::Grid;
+use itertools::Itertools;
+use line_search::GridLines;
+use render::{render, word_highlights, Highlight};
+
+use crate::{
+    grid_extensions::{GridExtensions, Orientation, Stencil, WordMatch},
+    options::{has_flag, value_of},
+};
+
+mod line_search;
+mod render;
+
+/// Options:
+///   --words A,B,...   search for these words instead, printing how many times each appears and where
+///   --bench           time searching for the words cell by cell against searching every line at once,
+///                     using every four letter word made of X, M, A and S unless --words says otherwise
+///   --show xmas|x-mas the grid with only the letters of XMAS (or the --words) or X-MAS matches showing
+///   --colour          colour the letters shown by the direction they read in
+pub fn run(options: &[String]) -> Result<(), Box<dyn Error>> {
+    let grid = load_grid(include_str!("../inputs/day4.txt"));
+    let words = value_of(options, "--words")?.map(|w| w.split(',').collect::<Vec<_>>());
+
+    if let Some(show) = value_of(options, "--show")? {
+        let colour = has_flag(options, "--colour");
+        let highlights = match show {
+            "xmas" => {
+                let words = words.unwrap_or_else(|| vec!["XMAS"]);
+                word_highlights(&grid, &GridLines::new(&grid).search(&words)?, colour)
+            }
+            "x-mas" => x_mas_highlights(&grid, colour),
+            _ => return Err(format!("Don't know how to show '{}'", show).into()),
+        };
+        print!("{}", render(&grid, &highlights));
+        return Ok(());
+    }
+
+    if has_flag(options, "--bench") {
+        let dictionary = std::iter::repeat_n(['X', 'M', 'A', 'S'], 4)
+            .multi_cartesian_product()
+            .map(|w| w.into_iter().collect::<String>())
+            .collect::<Vec<_>>();
+        let words =
+            words.unwrap_or_else(|| dictionary.iter().map(|w| w.as_str()).collect::<Vec<_>>());
+        return bench(&grid, &words);
+    }
+
+    if let Some(words) = words {
+        for found in GridLines::new(&grid).search(&words)? {
+            println!("{}: {} times", found.word, found.matches.len());
+            for m in found.matches {
+                println!("  from {:?} going {:?}", m.start, m.direction);
+            }
+        }
+        return Ok(());
+    }
+
+    let part1 = count_xmas(&grid);
+    println!("Part 1: XMAS appears {} times", part1);
+
+    let part2 = count_x_mas(&grid);
+    println!("Part 1: X-MAS appears {} times", part2);
+
+    Ok(())
+}
+
+fn load_grid(input: &str) -> Grid<char> {
+    let mut letters = Vec::new();
+    let mut line_width = 0;
+
+    for line in input.lines().map(|l| l.trim()) {
+        line_width = line.chars().count();
+        letters.extend(line.chars());
+    }
+
+    Grid::from_vec(letters, line_width)
+}
+
+fn all_of<'a>(
+    grid: &'a Grid<char>,
+    needle: &'a char,
+) -> impl Iterator<Item = (usize, usize)> + use<'a> {
+    grid.indexed_iter()
+        .filter_map(move |(p, letter)| if letter == needle { Some(p) } else { None })
+}
+
+/// Every place a word appears in the grid
+#[derive(Clone)]
+struct WordResults {
+    word: String,
+    matches: Vec<WordMatch>,
+}
+
+/// Looks for each of the words, which can be made of any characters at all, one cell at a time.
+/// A word appearing more than once in the list is only searched for once.
+fn search(grid: &Grid<char>, words: &[&str]) -> Vec<WordResults> {
+    words
+        .iter()
+        .unique()
+        .map(|word| WordResults {
+            word: word.to_string(),
+            matches: grid.find_word(&word.chars().collect::<Vec<_>>()),
+        })
+        .collect()
+}
+
+/// Compares how long searching for the words takes cell by cell and line by line
+fn bench(grid: &Grid<char>, words: &[&str]) -> Result<(), Box<dyn Error>> {
+    let start = Instant::now();
+    let by_cell = search(grid, words);
+    let by_cell_time = start.elapsed();
+
+    let start = Instant::now();
+    let by_line = GridLines::new(grid).search(words)?;
+    let by_line_time = start.elapsed();
+
+    let count = |results: &[WordResults]| results.iter().map(|r| r.matches.len()).sum::<usize>();
+    println!(
+        "{} words: {} matches cell by cell in {:?}, {} matches line by line in {:?}",
+        by_cell.len(),
+        count(&by_cell),
+        by_cell_time,
+        count(&by_line),
+        by_line_time
+    );
+    Ok(())
+}
+
+fn count_xmas(grid: &Grid<char>) -> usize {
+    grid.find_word(&['X', 'M', 'A', 'S']).len()
+}
+
+/// Two MASes crossing on the A, in any rotation
+fn x_mas_stencil() -> Stencil<char> {
+    Grid::from_vec(
+        vec![
+            Some('M'),
+            None,
+            Some('S'),
+            None,
+            Some('A'),
+            None,
+            Some('M'),
+            None,
+            Some('S'),
+        ],
+        3,
+    )
+}
+
+fn count_x_mas(grid: &Grid<char>) -> usize {
+    all_of(grid, &'A').filter(|p| is_x_mas(grid, *p)).count()
+}
+
+/// Shows the five letters of every X-MAS. They don't read in any one direction, so they're all the same colour.
+fn x_mas_highlights(grid: &Grid<char>, colour: bool) -> Grid<Highlight> {
+    let highlight = if colour {
+        Highlight::Coloured("\x1b[33m")
+    } else {
+        Highlight::Shown
+    };
+    let mut highlights = Grid::init(grid.rows(), grid.cols(), Highlight::Hidden);
+    for (row, col) in all_of(grid, &'A').filter(|p| is_x_mas(grid, *p)) {
+        for pos in [
+            (row - 1, col - 1),
+            (row - 1, col + 1),
+            (row, col),
+            (row + 1, col - 1),
+            (row + 1, col + 1),
+        ] {
+            highlights[pos] = highlight;
+        }
+    }
+    highlights
+}
+
+fn is_x_mas(grid: &Grid<char>, (row, col): (usize, usize)) -> bool {
+    if row == 0 || col == 0 {
+        // on the edge, not possible
+        return false;
+    }
+
+    let stencil = x_mas_stencil();
+    Orientation::ROTATIONS
+        .iter()
+        .any(|o| grid.stencil_matches_at(&stencil, *o, (row - 1, col - 1)))
+}
+
+#[cfg(test)]
+const TEST_INPUT: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+#[test]
+fn test_load_grid_easy() {
+    let input = "XMA
+S%!";
+    let grid = load_grid(input);
+    assert_eq!(grid, grid::grid![['X', 'M', 'A']['S', '%', '!']]);
+}
+
+#[test]
+fn test_find_xs() {
+    let input = "XMA
+SXX";
+    let grid = load_grid(input);
+    let xs = all_of(&grid, &'X').collect::<Vec<_>>();
+    assert_eq!(xs, vec![(0, 0), (1, 1), (1, 2)]);
+}
+
+#[test]
+fn test_part1() {
+    let grid = load_grid(TEST_INPUT);
+    let all_xmas = count_xmas(&grid);
+    assert_eq!(all_xmas, 18);
+}
+
+#[test]
+fn test_all_xmas_simple() {
+    let grid = load_grid(
+        "..X...
+.SAMX.
+.A..A.
+XMAS.S
+.X....",
+    );
+    let all_xmas = count_xmas(&grid);
+    assert_eq!(all_xmas, 4);
+}
+
+#[test]
+fn test_is_xmas() {
+    let grid = load_grid(
+        "M.S
+.A.
+M.S",
+    );
+
+    assert!(is_x_mas(&grid, (1, 1)));
+    assert!(!is_x_mas(&grid, (0, 0)));
+    assert!(!is_x_mas(&grid, (1, 2)));
+
+    let grid = load_grid(
+        "S.S
+.A.
+M.M",
+    );
+
+    assert!(is_x_mas(&grid, (1, 1)));
+
+    let grid = load_grid(
+        "S.S
+.A.
+M.S",
+    );
+
+    assert!(!is_x_mas(&grid, (1, 1)));
+}
+
+#[test]
+fn test_search_any_words() {
+    let grid = load_grid(TEST_INPUT);
+    let results = search(&grid, &["XMAS", "SAMX", "MAM", "X", "XMAS", "ZZ"]);
+    assert_eq!(
+        results
+            .iter()
+            .map(|r| (r.word.as_str(), r.matches.len()))
+            .collect::<Vec<_>>(),
+        vec![("XMAS", 18), ("SAMX", 18), ("MAM", 6), ("X", 19), ("ZZ", 0)]
+    );
+
+    let grid = load_grid("éé€\n€€é");
+    let results = search(&grid, &["é€", "€é€"]);
+    assert_eq!(results[0].matches.len(), 7);
+    assert_eq!(results[1].matches.len(), 0);
+}
+
+#[test]
+fn test_show_x_mas() {
+    let grid = load_grid(TEST_INPUT);
+    assert_eq!(
+        render(&grid, &x_mas_highlights(&grid, false)),
+        ".M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+"
+    );
+}
@@ -0,0 +1,107 @@
+use grid::Grid;
+
+use crate::grid_extensions::{Direction, GridExtensions};
+
+use super::WordResults;
+
+const RESET_COLOUR: &str = "\x1b[0m";
+
+/// How a cell of the grid is drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    /// Drawn as a `.`
+    Hidden,
+    Shown,
+    /// Shown in an ANSI colour
+    Coloured(&'static str),
+}
+
+/// A different colour for each direction a word can be read in
+fn direction_colour(direction: Direction) -> &'static str {
+    match direction {
+        Direction::North => "\x1b[31m",
+        Direction::NorthEast => "\x1b[32m",
+        Direction::East => "\x1b[33m",
+        Direction::SouthEast => "\x1b[34m",
+        Direction::South => "\x1b[35m",
+        Direction::SouthWest => "\x1b[36m",
+        Direction::West => "\x1b[91m",
+        Direction::NorthWest => "\x1b[92m",
+    }
+}
+
+/// Shows every letter of every match of the words, coloured by the direction it reads in if wanted.
+/// Where matches going different ways cross, the last one wins.
+pub fn word_highlights(grid: &Grid<char>, found: &[WordResults], colour: bool) -> Grid<Highlight> {
+    let mut highlights = Grid::init(grid.rows(), grid.cols(), Highlight::Hidden);
+    for word in found {
+        let letters = word.word.chars().count();
+        for m in word.matches.iter() {
+            let highlight = if colour {
+                Highlight::Coloured(direction_colour(m.direction))
+            } else {
+                Highlight::Shown
+            };
+            for (pos, _) in grid
+                .line_from(m.start.0, m.start.1, m.direction)
+                .limit(letters)
+            {
+                highlights[pos] = highlight;
+            }
+        }
+    }
+    highlights
+}
+
+/// The grid with only the highlighted letters showing, as in the puzzle's own illustrations
+pub fn render(grid: &Grid<char>, highlights: &Grid<Highlight>) -> String {
+    let mut rendered = String::new();
+    for (row, line) in grid.iter_rows().enumerate() {
+        for (col, c) in line.enumerate() {
+            match highlights[(row, col)] {
+                Highlight::Hidden => rendered.push('.'),
+                Highlight::Shown => rendered.push(*c),
+                Highlight::Coloured(colour) => {
+                    rendered.push_str(colour);
+                    rendered.push(*c);
+                    rendered.push_str(RESET_COLOUR);
+                }
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+#[test]
+fn test_render_xmas() {
+    let grid = super::load_grid(super::TEST_INPUT);
+    let found = super::search(&grid, &["XMAS"]);
+    let highlights = word_highlights(&grid, &found, false);
+    assert_eq!(
+        render(&grid, &highlights),
+        "....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+"
+    );
+}
+
+#[test]
+fn test_render_in_colour() {
+    let grid = super::load_grid("XMAS\nSAMX");
+    let found = super::search(&grid, &["XMAS"]);
+    let highlights = word_highlights(&grid, &found, true);
+    assert_eq!(
+        render(&grid, &highlights),
+        "\x1b[33mX\x1b[0m\x1b[33mM\x1b[0m\x1b[33mA\x1b[0m\x1b[33mS\x1b[0m\n\
+         \x1b[91mS\x1b[0m\x1b[91mA\x1b[0m\x1b[91mM\x1b[0m\x1b[91mX\x1b[0m\n"
+    );
+}
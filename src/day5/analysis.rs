@@ -0,0 +1,281 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use super::{Rule, RuleSet, UpdateGraph};
+
+/// How many ways the pages in an update can go, according to the rules about them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOrder {
+    Unique,
+    /// Some pages could go either way round
+    Several,
+    /// The rules go round in a circle
+    Impossible,
+}
+
+impl UpdateOrder {
+    fn of(rules: &RuleSet, pages: &[u16]) -> Self {
+        let (ordered, unique) = UpdateGraph::new(rules, pages).topological_sort();
+        if ordered.len() < pages.len() {
+            UpdateOrder::Impossible
+        } else if unique {
+            UpdateOrder::Unique
+        } else {
+            UpdateOrder::Several
+        }
+    }
+}
+
+impl Display for UpdateOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateOrder::Unique => write!(f, "one order"),
+            UpdateOrder::Several => write!(f, "more than one order"),
+            UpdateOrder::Impossible => write!(f, "no order, the rules go round in a circle"),
+        }
+    }
+}
+
+/// Whether the rules hang together as a whole, rather than just for each update
+pub struct Analysis {
+    pub pages: usize,
+    pub rules: usize,
+    /// Groups of pages that the rules go round in circles between, from the strongly connected components of the
+    /// rule graph
+    pub cycles: Vec<Vec<u16>>,
+    /// Pages in the updates that no rule mentions
+    pub unruled_pages: Vec<u16>,
+    /// Rules that follow from the others, because there's another way through the rules from the first page to
+    /// the second
+    pub redundant_rules: Vec<Rule>,
+    pub update_orders: Vec<UpdateOrder>,
+}
+
+impl Analysis {
+    pub fn new(rules: &RuleSet, updates: &[Vec<u16>]) -> Self {
+        let pages = rules.pages();
+        let all_rules = rules.rules();
+
+        let mut cycles = strongly_connected_components(rules)
+            .into_iter()
+            .filter(|c| c.len() > 1 || rules.precedes(c[0], c[0]))
+            .collect::<Vec<_>>();
+        cycles.sort();
+
+        let mut unruled_pages = updates
+            .iter()
+            .flatten()
+            .filter(|p| pages.binary_search(p).is_err())
+            .copied()
+            .collect::<Vec<_>>();
+        unruled_pages.sort();
+        unruled_pages.dedup();
+
+        let redundant_rules = all_rules
+            .iter()
+            .filter(|r| is_implied_by_others(rules, r))
+            .copied()
+            .collect();
+
+        let update_orders = updates.iter().map(|u| UpdateOrder::of(rules, u)).collect();
+
+        Analysis {
+            pages: pages.len(),
+            rules: all_rules.len(),
+            cycles,
+            unruled_pages,
+            redundant_rules,
+            update_orders,
+        }
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |items: Vec<String>| items.join(", ");
+
+        writeln!(f, "{} rules about {} pages", self.rules, self.pages)?;
+
+        if self.cycles.is_empty() {
+            writeln!(f, "\nThe rules never go round in a circle")?;
+        } else {
+            writeln!(f, "\nThe rules go round in circles between these pages:")?;
+            for cycle in self.cycles.iter() {
+                writeln!(
+                    f,
+                    "  {} ({} pages)",
+                    list(cycle.iter().map(|p| p.to_string()).collect()),
+                    cycle.len()
+                )?;
+            }
+        }
+
+        if self.unruled_pages.is_empty() {
+            writeln!(f, "\nEvery page in the updates has rules about it")?;
+        } else {
+            writeln!(
+                f,
+                "\nPages in the updates without any rules: {}",
+                list(self.unruled_pages.iter().map(|p| p.to_string()).collect())
+            )?;
+        }
+
+        if self.redundant_rules.is_empty() {
+            writeln!(f, "\nNone of the rules follow from the others")?;
+        } else {
+            writeln!(
+                f,
+                "\n{} rules follow from the others:",
+                self.redundant_rules.len()
+            )?;
+            for rules in self.redundant_rules.chunks(12) {
+                writeln!(
+                    f,
+                    "  {}",
+                    list(rules.iter().map(|r| r.to_string()).collect())
+                )?;
+            }
+        }
+
+        writeln!(f, "\nUpdates:")?;
+        for (i, order) in self.update_orders.iter().enumerate() {
+            writeln!(f, "  {:>4}: {}", i + 1, order)?;
+        }
+        Ok(())
+    }
+}
+
+/// Is there a way from the rule's first page to its second without using the rule itself?
+fn is_implied_by_others(rules: &RuleSet, rule: &Rule) -> bool {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([rule.page]);
+    while let Some(page) = queue.pop_front() {
+        for next in rules.followers_of(page) {
+            if page == rule.page && next == rule.must_be_before {
+                continue;
+            }
+            if next == rule.must_be_before {
+                return true;
+            }
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    false
+}
+
+/// Tarjan's algorithm, giving each group of pages that can all be reached from each other through the rules
+fn strongly_connected_components(rules: &RuleSet) -> Vec<Vec<u16>> {
+    let mut tarjan = Tarjan {
+        rules,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for page in rules.pages() {
+        if !tarjan.index.contains_key(&page) {
+            tarjan.visit(page);
+        }
+    }
+    tarjan.components
+}
+
+struct Tarjan<'a> {
+    rules: &'a RuleSet,
+    /// The order pages were first visited in
+    index: HashMap<u16, usize>,
+    /// The earliest visited page on the stack that a page is known to reach
+    low_link: HashMap<u16, usize>,
+    stack: Vec<u16>,
+    on_stack: HashSet<u16>,
+    components: Vec<Vec<u16>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, page: u16) {
+        let index = self.index.len();
+        self.index.insert(page, index);
+        self.low_link.insert(page, index);
+        self.stack.push(page);
+        self.on_stack.insert(page);
+
+        for next in self.rules.followers_of(page) {
+            let reached = if !self.index.contains_key(&next) {
+                self.visit(next);
+                self.low_link[&next]
+            } else if self.on_stack.contains(&next) {
+                self.index[&next]
+            } else {
+                continue;
+            };
+            let low_link = self.low_link[&page].min(reached);
+            self.low_link.insert(page, low_link);
+        }
+
+        if self.low_link[&page] == index {
+            let mut component = Vec::new();
+            loop {
+                let p = self
+                    .stack
+                    .pop()
+                    .expect("The page itself is still on the stack");
+                self.on_stack.remove(&p);
+                component.push(p);
+                if p == page {
+                    break;
+                }
+            }
+            component.sort();
+            self.components.push(component);
+        }
+    }
+}
+
+#[test]
+fn test_analysis() {
+    let rules = RuleSet::new(
+        &super::parse_rules("1|2\n2|3\n3|1\n3|4\n4|5\n4|6\n5|6\n7|7").expect("These should parse"),
+    );
+    let updates = vec![vec![4, 5, 6], vec![4, 6, 5], vec![5, 6, 9], vec![1, 2, 3]];
+    let analysis = Analysis::new(&rules, &updates);
+
+    assert_eq!(analysis.pages, 7);
+    assert_eq!(analysis.rules, 8);
+    assert_eq!(analysis.cycles, vec![vec![1, 2, 3], vec![7]]);
+    assert_eq!(analysis.unruled_pages, vec![9]);
+    assert_eq!(
+        analysis.redundant_rules,
+        vec![Rule {
+            page: 4,
+            must_be_before: 6
+        }]
+    );
+    assert_eq!(
+        analysis.update_orders,
+        vec![
+            UpdateOrder::Unique,
+            UpdateOrder::Unique,
+            UpdateOrder::Several,
+            UpdateOrder::Impossible
+        ]
+    );
+}
+
+#[test]
+fn test_puzzle_example_analysis() {
+    let analysis = Analysis::new(
+        &super::test_rule_set(),
+        &super::parse_updates(super::TEST_PAGES).unwrap(),
+    );
+    assert!(analysis.cycles.is_empty());
+    assert!(analysis.unruled_pages.is_empty());
+    assert!(analysis
+        .update_orders
+        .iter()
+        .all(|o| *o == UpdateOrder::Unique));
+}
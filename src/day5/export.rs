@@ -0,0 +1,151 @@
+use super::{Rule, RuleSet};
+
+/// The rules as a graph to draw, with an edge from each page to the pages it has to come before, labelled with the
+/// rule
+pub struct RuleGraph {
+    pages: Vec<u16>,
+    edges: Vec<Rule>,
+    /// Rules the update being drawn breaks, which are drawn in red
+    violated: Vec<Rule>,
+}
+
+impl RuleGraph {
+    /// Every rule there is
+    pub fn all(rules: &RuleSet) -> Self {
+        RuleGraph {
+            pages: rules.pages(),
+            edges: rules.rules(),
+            violated: Vec::new(),
+        }
+    }
+
+    /// Only the rules about two pages that are both in the update, with its pages in the order it has them
+    pub fn for_update(rules: &RuleSet, pages: &[u16]) -> Self {
+        let mut edges = Vec::new();
+        let mut violated = Vec::new();
+        for (first, page) in pages.iter().enumerate() {
+            for (second, must_be_before) in pages.iter().enumerate() {
+                if rules.precedes(*page, *must_be_before) {
+                    let rule = Rule {
+                        page: *page,
+                        must_be_before: *must_be_before,
+                    };
+                    edges.push(rule);
+                    if second < first {
+                        violated.push(rule);
+                    }
+                }
+            }
+        }
+        RuleGraph {
+            pages: pages.to_vec(),
+            edges,
+            violated,
+        }
+    }
+
+    /// Graphviz DOT
+    pub fn dot(&self) -> String {
+        let mut dot = String::from("digraph rules {\n");
+        for page in self.pages.iter() {
+            dot.push_str(&format!("    {};\n", page));
+        }
+        for rule in self.edges.iter() {
+            let colour = if self.violated.contains(rule) {
+                ", color=red, fontcolor=red"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{}\"{}];\n",
+                rule.page, rule.must_be_before, rule, colour
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// A Mermaid flowchart
+    pub fn mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for page in self.pages.iter() {
+            mermaid.push_str(&format!("    p{}[\"{}\"]\n", page, page));
+        }
+        for rule in self.edges.iter() {
+            mermaid.push_str(&format!(
+                "    p{} -- \"{}\" --> p{}\n",
+                rule.page, rule, rule.must_be_before
+            ));
+        }
+        // Mermaid styles links by the order they were declared in
+        let violated = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| self.violated.contains(rule))
+            .map(|(i, _)| i.to_string())
+            .collect::<Vec<_>>();
+        if !violated.is_empty() {
+            mermaid.push_str(&format!(
+                "    linkStyle {} stroke:red,color:red\n",
+                violated.join(",")
+            ));
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+fn test_rules() -> RuleSet {
+    RuleSet::new(&super::parse_rules("1|2\n2|3\n1|3\n3|4").expect("These should parse"))
+}
+
+#[test]
+fn test_dot() {
+    assert_eq!(
+        RuleGraph::all(&test_rules()).dot(),
+        "digraph rules {
+    1;
+    2;
+    3;
+    4;
+    1 -> 2 [label=\"1|2\"];
+    1 -> 3 [label=\"1|3\"];
+    2 -> 3 [label=\"2|3\"];
+    3 -> 4 [label=\"3|4\"];
+}
+"
+    );
+
+    assert_eq!(
+        RuleGraph::for_update(&test_rules(), &[3, 1, 2]).dot(),
+        "digraph rules {
+    3;
+    1;
+    2;
+    1 -> 3 [label=\"1|3\", color=red, fontcolor=red];
+    1 -> 2 [label=\"1|2\"];
+    2 -> 3 [label=\"2|3\", color=red, fontcolor=red];
+}
+"
+    );
+}
+
+#[test]
+fn test_mermaid() {
+    assert_eq!(
+        RuleGraph::for_update(&test_rules(), &[3, 1, 2]).mermaid(),
+        "flowchart LR
+    p3[\"3\"]
+    p1[\"1\"]
+    p2[\"2\"]
+    p1 -- \"1|3\" --> p3
+    p1 -- \"1|2\" --> p2
+    p2 -- \"2|3\" --> p3
+    linkStyle 0,2 stroke:red,color:red
+"
+    );
+    assert!(!RuleGraph::for_update(&test_rules(), &[1, 2, 3])
+        .mermaid()
+        .contains("linkStyle"));
+}
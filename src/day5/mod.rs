@@ -0,0 +1,574 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt::Display,
+    str::FromStr,
+};
+
+use analysis::Analysis;
+use export::RuleGraph;
+
+use crate::options::{has_flag, optional_value_of};
+
+mod analysis;
+mod export;
+
+/// Options:
+///   --analyse      report circles in the rules, pages without rules, rules that follow from the others and
+///                  whether each update has only one order
+///   --dot [N]      print the rules as a Graphviz DOT graph, or only the ones about update N's pages with the
+///                  rules it breaks in red
+///   --mermaid [N]  the same as a Mermaid flowchart
+pub fn run(options: &[String]) -> Result<(), Box<dyn Error>> {
+    let rules = RuleSet::new(&parse_rules(include_str!("../inputs/day5/rules.txt"))?);
+    let updates = parse_updates(include_str!("../inputs/day5/updates.txt"))?;
+
+    if has_flag(options, "--analyse") {
+        print!("{}", Analysis::new(&rules, &updates));
+        return Ok(());
+    }
+
+    if let Some(update) = optional_value_of(options, "--dot") {
+        print!("{}", rule_graph(&rules, &updates, update)?.dot());
+        return Ok(());
+    }
+
+    if let Some(update) = optional_value_of(options, "--mermaid") {
+        print!("{}", rule_graph(&rules, &updates, update)?.mermaid());
+        return Ok(());
+    }
+
+    let part1 = part1(&rules, &updates);
+    println!("The result for part 1 is {part1}");
+
+    let part2 = part2(&rules, &updates)?;
+    println!("The result for part 2 is {part2}");
+
+    Ok(())
+}
+
+/// All the rules, or only those about the pages of the update with the given number, counting from 1
+fn rule_graph(
+    rules: &RuleSet,
+    updates: &[Vec<u16>],
+    update: Option<&str>,
+) -> Result<RuleGraph, Box<dyn Error>> {
+    let Some(update) = update else {
+        return Ok(RuleGraph::all(rules));
+    };
+    let pages = usize::from_str(update)
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| updates.get(i))
+        .ok_or_else(|| {
+            format!(
+                "There's no update '{}', they're numbered 1 to {}",
+                update,
+                updates.len()
+            )
+        })?;
+    Ok(RuleGraph::for_update(rules, pages))
+}
+
+fn part1(rules: &RuleSet, updates: &[Vec<u16>]) -> u32 {
+    updates
+        .iter()
+        .filter(|p| rules.is_satisfied_by(p))
+        .filter_map(|p| middle_of(p))
+        .map(|p| p as u32)
+        .sum()
+}
+
+fn part2(rules: &RuleSet, updates: &[Vec<u16>]) -> Result<u32, Box<dyn Error>> {
+    let mut total = 0;
+    for update in all_failed_updates(rules, updates) {
+        if let Some(middle) = fix_ordering_by_rules(rules, update)?.and_then(|p| middle_of(&p)) {
+            total += middle as u32;
+        }
+    }
+    Ok(total)
+}
+
+/// Fix the page ordering according to the rules, but only return something if the ordering needed fixing.
+/// Sorting by the rules works when they put the update's pages in a total order, as they do in the puzzle input.
+/// Otherwise only the rules about two pages that are both in the update matter. They make a graph with an edge
+/// from each page to the ones it has to come before, and the pages are put in order with a topological sort
+/// (Kahn's algorithm).
+/// It's an error if the rules go round in a circle, so there's no order that satisfies them all.
+fn fix_ordering_by_rules(
+    rules: &RuleSet,
+    pages: &[u16],
+) -> Result<Option<Vec<u16>>, Box<dyn Error>> {
+    if rules.is_satisfied_by(pages) {
+        return Ok(None);
+    }
+
+    if rules.is_total_order_on(pages) {
+        let mut sorted = pages.to_vec();
+        sorted.sort_by(|a, b| rules.compare(a, b));
+        return Ok(Some(sorted));
+    }
+
+    let mut graph = UpdateGraph::new(rules, pages);
+    let (ordered, _) = graph.topological_sort();
+    if ordered.len() < pages.len() {
+        return Err(format!(
+            "The rules for {:?} go round in a circle: {}",
+            pages,
+            graph.find_cycle(pages)
+        )
+        .into());
+    }
+
+    Ok(Some(ordered.into_iter().map(|i| pages[i]).collect()))
+}
+
+/// The rules about two pages that are both in an update, as a graph with an edge from each page to the ones it
+/// has to come before. Pages are referred to by their index in the update.
+struct UpdateGraph {
+    followers: Vec<Vec<usize>>,
+    /// How many pages that haven't been placed yet each page has to come after
+    preceders: Vec<usize>,
+}
+
+impl UpdateGraph {
+    fn new(rules: &RuleSet, pages: &[u16]) -> Self {
+        let mut followers = vec![Vec::new(); pages.len()];
+        let mut preceders = vec![0; pages.len()];
+        for (first, a) in pages.iter().enumerate() {
+            for (second, b) in pages.iter().enumerate() {
+                if rules.precedes(*a, *b) {
+                    followers[first].push(second);
+                    preceders[second] += 1;
+                }
+            }
+        }
+        UpdateGraph {
+            followers,
+            preceders,
+        }
+    }
+
+    /// Kahn's algorithm. Returns as many pages as could be placed, in order, and whether there was only ever one
+    /// page ready to place, in which case that's the only order there is.
+    /// Pages that couldn't be placed because the rules go round in a circle are left with preceders.
+    fn topological_sort(&mut self) -> (Vec<usize>, bool) {
+        let mut ready = (0..self.preceders.len())
+            .filter(|i| self.preceders[*i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut ordered = Vec::with_capacity(self.preceders.len());
+        let mut unique = true;
+        while let Some(i) = ready.pop_front() {
+            unique &= ready.is_empty();
+            ordered.push(i);
+            for f in self.followers[i].iter() {
+                self.preceders[*f] -= 1;
+                if self.preceders[*f] == 0 {
+                    ready.push_back(*f);
+                }
+            }
+        }
+        (ordered, unique)
+    }
+
+    /// Once a topological sort has got stuck, every page it couldn't place still has a page before it that
+    /// couldn't be placed either. Walking backwards along those must come round in a circle eventually.
+    fn find_cycle(&self, pages: &[u16]) -> String {
+        let stuck = |i: usize| self.preceders[i] > 0;
+        let before = |i: usize| {
+            (0..pages.len())
+                .find(|p| stuck(*p) && self.followers[*p].contains(&i))
+                .expect("Every stuck page has a stuck page before it")
+        };
+
+        let mut seen = vec![false; pages.len()];
+        let mut at = (0..pages.len())
+            .find(|i| stuck(*i))
+            .expect("There's at least one stuck page");
+        while !seen[at] {
+            seen[at] = true;
+            at = before(at);
+        }
+
+        // `at` is on the circle, so go round it once more to collect it
+        let mut cycle = vec![at];
+        let mut previous = before(at);
+        while previous != at {
+            cycle.push(previous);
+            previous = before(previous);
+        }
+        cycle.reverse();
+        cycle.push(cycle[0]);
+
+        cycle
+            .windows(2)
+            .map(|w| {
+                Rule {
+                    page: pages[w[0]],
+                    must_be_before: pages[w[1]],
+                }
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn all_failed_updates<'a>(rules: &RuleSet, updates: &'a [Vec<u16>]) -> Vec<&'a [u16]> {
+    updates
+        .iter()
+        .filter(|u| !rules.is_satisfied_by(u))
+        .map(|u| u.as_slice())
+        .collect()
+}
+
+fn middle_of<T: Clone>(pages: &[T]) -> Option<T> {
+    if pages.is_empty() || pages.len().is_multiple_of(2) {
+        // doesn't have a middle
+        None
+    } else {
+        Some(pages[pages.len() / 2].clone())
+    }
+}
+
+fn parse_rules(input: &str) -> Result<Vec<Rule>, Box<dyn Error>> {
+    input
+        .lines()
+        .map(Rule::from_str)
+        .collect::<Result<Vec<Rule>, _>>()
+}
+
+fn parse_updates(input: &str) -> Result<Vec<Vec<u16>>, Box<dyn Error>> {
+    Ok(input
+        .lines()
+        .map(|l| {
+            l.split(',')
+                .map(u16::from_str)
+                .collect::<Result<Vec<u16>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+lazy_static! {
+    static ref RULE_REGEX: Regex =
+        Regex::new(r"(\d+)\|(\d+)").expect("You should learn to write regex properly");
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Rule {
+    page: u16,
+    must_be_before: u16,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.page, self.must_be_before)
+    }
+}
+
+/// The rules indexed by page, so whether one page has to come before another is a single lookup
+struct RuleSet {
+    /// The pages each page has to come before
+    followers: HashMap<u16, HashSet<u16>>,
+}
+
+impl RuleSet {
+    fn new(rules: &[Rule]) -> Self {
+        let mut followers = HashMap::<u16, HashSet<u16>>::new();
+        for rule in rules {
+            followers
+                .entry(rule.page)
+                .or_default()
+                .insert(rule.must_be_before);
+        }
+        RuleSet { followers }
+    }
+
+    /// Whether there's a rule that page `a` comes before page `b`
+    fn precedes(&self, a: u16, b: u16) -> bool {
+        self.followers.get(&a).is_some_and(|f| f.contains(&b))
+    }
+
+    /// No page comes after a page that the rules say it has to come before
+    fn is_satisfied_by(&self, pages: &[u16]) -> bool {
+        pages.iter().enumerate().all(|(i, later)| {
+            pages[..i]
+                .iter()
+                .all(|earlier| !self.precedes(*later, *earlier))
+        })
+    }
+
+    /// Orders two pages by the rule between them, if there is one, for sorting an update with `sort_by`.
+    /// Pages without a rule between them compare as equal, so this is only a total order, which `sort_by` needs,
+    /// for updates that `is_total_order_on` says yes to. Otherwise it takes a topological sort.
+    fn compare(&self, a: &u16, b: &u16) -> Ordering {
+        if self.precedes(*a, *b) {
+            Ordering::Less
+        } else if self.precedes(*b, *a) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Whether there's a rule between every two pages in the update and they never go round in a circle.
+    /// That's when each page has to come before a different number of the others, from none up to all of them.
+    fn is_total_order_on(&self, pages: &[u16]) -> bool {
+        let mut seen = vec![false; pages.len()];
+        for (i, a) in pages.iter().enumerate() {
+            let mut before = 0;
+            for (j, b) in pages.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                match (self.precedes(*a, *b), self.precedes(*b, *a)) {
+                    (true, false) => before += 1,
+                    (false, true) => {}
+                    _ => return false,
+                }
+            }
+            if std::mem::replace(&mut seen[before], true) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Every page any rule mentions, in order
+    fn pages(&self) -> Vec<u16> {
+        let mut pages = self
+            .followers
+            .iter()
+            .flat_map(|(page, followers)| followers.iter().chain([page]))
+            .copied()
+            .collect::<Vec<_>>();
+        pages.sort();
+        pages.dedup();
+        pages
+    }
+
+    /// The pages a page has to come before, in order
+    fn followers_of(&self, page: u16) -> Vec<u16> {
+        let mut followers = self
+            .followers
+            .get(&page)
+            .map(|f| f.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        followers.sort();
+        followers
+    }
+
+    /// Every rule, in order
+    fn rules(&self) -> Vec<Rule> {
+        self.pages()
+            .into_iter()
+            .flat_map(|page| {
+                self.followers_of(page)
+                    .into_iter()
+                    .map(move |must_be_before| Rule {
+                        page,
+                        must_be_before,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = RULE_REGEX.captures(s) {
+            Ok(Rule {
+                page: u16::from_str(&c[1])?,
+                must_be_before: u16::from_str(&c[2])?,
+            })
+        } else {
+            Err(format!("'{s}' didn't parse as a rule").into())
+        }
+    }
+}
+
+#[cfg(test)]
+const TEST_RULES: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13";
+#[cfg(test)]
+const TEST_PAGES: &str = "75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+#[cfg(test)]
+fn test_rule_set() -> RuleSet {
+    RuleSet::new(&parse_rules(TEST_RULES).expect("These should parse"))
+}
+
+#[test]
+fn test_are_satisfied_by() {
+    let rules = test_rule_set();
+    let pages = parse_updates(TEST_PAGES).expect("These should parse");
+
+    assert!(rules.is_satisfied_by(&pages[0]));
+    assert!(rules.is_satisfied_by(&pages[1]));
+    assert!(rules.is_satisfied_by(&pages[2]));
+    assert!(!rules.is_satisfied_by(&pages[3]));
+    assert!(!rules.is_satisfied_by(&pages[4]));
+    assert!(!rules.is_satisfied_by(&pages[5]));
+}
+
+#[test]
+fn test_part1() {
+    let rules = test_rule_set();
+    let pages = parse_updates(TEST_PAGES).expect("These should parse");
+
+    assert_eq!(part1(&rules, &pages), 143);
+}
+
+#[test]
+fn test_is_satisfied_by() {
+    let rules = RuleSet::new(&[Rule {
+        page: 7,
+        must_be_before: 4,
+    }]);
+    assert!(rules.is_satisfied_by(&[44, 2, 38, 0, 2, 7, 4]));
+    assert!(!rules.is_satisfied_by(&[44, 4, 38, 0, 2, 7]));
+}
+
+#[test]
+fn test_precedes_and_compare() {
+    let rules = test_rule_set();
+    assert!(rules.precedes(47, 53));
+    assert!(!rules.precedes(53, 47));
+    assert!(!rules.precedes(47, 47));
+    assert!(!rules.precedes(1, 2));
+
+    assert_eq!(rules.compare(&97, &13), Ordering::Less);
+    assert_eq!(rules.compare(&13, &97), Ordering::Greater);
+    assert_eq!(rules.compare(&1, &97), Ordering::Equal);
+
+    let mut pages = vec![61, 13, 29];
+    pages.sort_by(|a, b| rules.compare(a, b));
+    assert_eq!(pages, vec![61, 29, 13]);
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules = parse_rules(TEST_RULES).expect("These should parse");
+    assert_eq!(rules.len(), 21);
+    assert_eq!(rules[20].page, 53);
+    assert_eq!(rules[20].must_be_before, 13);
+}
+
+#[test]
+fn test_parse_pages() {
+    let pages = parse_updates(TEST_PAGES).expect("These should parse");
+    assert_eq!(pages.len(), 6);
+    assert_eq!(pages[1], vec![97, 61, 53, 29, 13]);
+}
+
+#[test]
+fn test_part2() {
+    let rules = test_rule_set();
+    let pages = parse_updates(TEST_PAGES).expect("These should parse");
+
+    assert_eq!(part2(&rules, &pages).unwrap(), 123);
+}
+
+#[test]
+fn test_fix_ordering() {
+    let rules = test_rule_set();
+    assert_eq!(
+        fix_ordering_by_rules(&rules, &[97, 13, 75, 29, 47]).unwrap(),
+        Some(vec![97, 75, 47, 29, 13])
+    );
+    assert_eq!(
+        fix_ordering_by_rules(&rules, &[75, 97, 47, 61, 53]).unwrap(),
+        Some(vec![97, 75, 47, 61, 53])
+    );
+    assert_eq!(fix_ordering_by_rules(&rules, &[75, 29, 13]).unwrap(), None);
+}
+
+#[test]
+fn test_fix_ordering_with_cycle() {
+    let rules = RuleSet::new(&parse_rules("1|2\n2|3\n3|1\n4|1").expect("These should parse"));
+    let error =
+        fix_ordering_by_rules(&rules, &[4, 3, 2, 1]).expect_err("There's no order for these");
+    assert_eq!(
+        error.to_string(),
+        "The rules for [4, 3, 2, 1] go round in a circle: 1|2, 2|3, 3|1"
+    );
+
+    // a circle among pages that aren't all in the update doesn't matter
+    assert_eq!(
+        fix_ordering_by_rules(&rules, &[2, 1]).unwrap(),
+        Some(vec![1, 2])
+    );
+}
+
+#[test]
+fn test_fix_ordering_with_partial_rules() {
+    // two chains, 1|3|5... and 2|4|6..., with nothing saying how they go together
+    let chains = (1..=28)
+        .map(|p| Rule {
+            page: p,
+            must_be_before: p + 2,
+        })
+        .collect::<Vec<_>>();
+    let rules = RuleSet::new(&chains);
+    let pages = (1..=30).rev().collect::<Vec<u16>>();
+    assert!(!rules.is_total_order_on(&pages));
+
+    let fixed = fix_ordering_by_rules(&rules, &pages)
+        .unwrap()
+        .expect("This needed fixing");
+    assert_eq!(fixed.len(), 30);
+    assert!(rules.is_satisfied_by(&fixed));
+
+    let mut circle = chains;
+    circle.push(Rule {
+        page: 29,
+        must_be_before: 1,
+    });
+    assert!(fix_ordering_by_rules(&RuleSet::new(&circle), &pages).is_err());
+
+    // every pair of pages has a rule, but they go round in a circle
+    let mut tournament = (1..=30)
+        .flat_map(|a| {
+            (a + 1..=30).map(move |b| Rule {
+                page: a,
+                must_be_before: b,
+            })
+        })
+        .filter(|r| (r.page, r.must_be_before) != (1, 30))
+        .collect::<Vec<_>>();
+    tournament.push(Rule {
+        page: 30,
+        must_be_before: 1,
+    });
+    let rules = RuleSet::new(&tournament);
+    assert!(!rules.is_total_order_on(&pages));
+    assert!(fix_ordering_by_rules(&rules, &pages).is_err());
+}
@@ -1,5 +1,9 @@
+use crate::grid_extensions::{
+    bit_grid::{BitGrid, DirectionGrid},
+    Direction, GridExtensions, RayHit,
+};
 use grid::Grid;
-use std::{collections::HashSet, error::Error};
+use std::error::Error;
 
 pub fn run() -> Result<(), Box<dyn Error>> {
     let input = include_str!("inputs/day6.txt");
@@ -7,40 +11,48 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     let start_pos =
         find_start_pos(input).ok_or_else(|| "Unable to find start position".to_owned())?;
 
-    let part1 = part1(map.clone(), start_pos);
+    let part1 = part1(&map, start_pos);
 
     println!("Part 1: {part1} spaces visited");
 
+    let part2 = part2(map, start_pos);
+
+    println!("Part 2: {part2} obstruction positions cause a loop");
+
     Ok(())
 }
 
-fn part1(mut map: Map, pos: (usize, usize)) -> usize {
-    patrol(&mut map, pos);
-    map.0.iter().filter(|s| s == &&MapSquare::Visited).count()
+fn part1(map: &Map, pos: (usize, usize)) -> usize {
+    let mut turns = DirectionGrid::new(map.0.rows(), map.0.cols());
+    let mut visited = BitGrid::new(map.0.rows(), map.0.cols());
+    patrol(map, pos, &mut turns, Some(&mut visited));
+    visited.count()
 }
 
-/// Runs patrols by inserting a random new obstruction into the map and seeing if it makes a loop
+/// Runs patrols by inserting a new obstruction into the map and seeing if it makes a loop
 /// Returns the count of how many different obstructions could be added which cause a loop
-/// This is brute-force and may prove far too slow to actually run in reality
-/// Initial testing shows it's surprisingly fast, but the loop detection appears to detect too many loops
-fn part2(map: Map, pos: (usize, usize)) -> usize {
-    let possible_obstruction_positions = map
-        .0
-        .indexed_iter()
-        .filter(|x| x.1 == &MapSquare::Empty)
-        .map(|x| x.0);
+/// Only squares the guard actually visits without any new obstruction are worth trying, and the guard's
+/// starting square is off limits
+fn part2(mut map: Map, pos: (usize, usize)) -> usize {
+    let mut turns = DirectionGrid::new(map.0.rows(), map.0.cols());
+    let mut possible_obstruction_positions = BitGrid::new(map.0.rows(), map.0.cols());
+    patrol(
+        &map,
+        pos,
+        &mut turns,
+        Some(&mut possible_obstruction_positions),
+    );
+    possible_obstruction_positions.remove(pos);
 
     let mut loop_causing_positions = 0;
 
-    for obstruction_pos in possible_obstruction_positions {
-        let mut obstructed_map = map.clone();
-        *(obstructed_map
-            .0
-            .get_mut(obstruction_pos.0, obstruction_pos.1)
-            .unwrap()) = MapSquare::Obstacle;
-        if patrol(&mut obstructed_map, pos) {
+    for obstruction_pos in possible_obstruction_positions.iter() {
+        map.0[obstruction_pos] = MapSquare::Obstacle;
+        turns.clear();
+        if patrol(&map, pos, &mut turns, None) {
             loop_causing_positions += 1;
         }
+        map.0[obstruction_pos] = MapSquare::Empty;
     }
 
     loop_causing_positions
@@ -66,73 +78,46 @@ fn find_start_pos(input: &str) -> Option<(usize, usize)> {
 
 /// Runs a guard patrol on the given map with the given starting position.
 /// Always starts facing north.
+/// Every square the guard turns on is recorded in `turns` along with the way they were facing beforehand, and
+/// if `visited` is given every square they walk through is recorded there.
 /// Returns true if the patrol ends with a loop
 /// Returns false if the patrol ends by leaving the map
-fn patrol(map: &mut Map, mut pos: (usize, usize)) -> bool {
-    let mut facing = Facing::North;
-    let mut step_cache = HashSet::new();
+fn patrol(
+    map: &Map,
+    mut pos: (usize, usize),
+    turns: &mut DirectionGrid,
+    mut visited: Option<&mut BitGrid>,
+) -> bool {
+    let mut facing = Direction::North;
 
     loop {
-        if let Some(r) = map.0.get_mut(pos.0, pos.1) {
-            *r = MapSquare::Visited;
-        }
-
-        let cache_pos = pos;
-        let instruction = step(map, pos, facing);
-        let cache_entry = (cache_pos.0, cache_pos.1, instruction.clone());
-        if step_cache.contains(&cache_entry) {
-            // looks like we're looping, because we've been in this position with this instruction before
-            return true;
-        }
-
-        step_cache.insert(cache_entry);
-
-        match step(map, pos, facing) {
-            Instruction::Turn => facing = facing.turn_right(),
-            Instruction::MoveTo { row, col } => pos = (row, col),
-            Instruction::LeaveArea => return false,
+        let ahead = map
+            .0
+            .cast_ray(pos.0, pos.1, facing, |s| *s == MapSquare::Obstacle);
+
+        if let Some(visited) = visited.as_deref_mut() {
+            let squares_walked = match ahead {
+                RayHit::Found { distance, .. } => distance,
+                RayHit::Exit { distance, .. } => distance + 1,
+            };
+            for (p, _) in map.0.line_from(pos.0, pos.1, facing).limit(squares_walked) {
+                visited.insert(p);
+            }
         }
-    }
-}
 
-fn step(map: &Map, guard_pos: (usize, usize), guard_facing: Facing) -> Instruction {
-    match map.coords_ahead(guard_pos, guard_facing) {
-        Some(ahead_pos) => match map.0.get(ahead_pos.0, ahead_pos.1) {
-            Some(ms) => match ms {
-                MapSquare::Obstacle => Instruction::Turn,
-                _ => Instruction::MoveTo { row: ahead_pos.0, col: ahead_pos.1 }
+        match ahead {
+            RayHit::Found { pos: obstacle, .. } => {
+                pos = facing
+                    .opposite()
+                    .step(obstacle, map.0.size())
+                    .expect("The guard should have been able to walk up to the obstacle");
+                if !turns.insert(pos, facing) {
+                    // looks like we're looping, because we've turned here facing this way before
+                    return true;
+                }
+                facing = facing.turn_right();
             }
-            None => panic!(
-                "{:?} wasn't a valid position in the map, which should be utterly impossible, there's a bug in Map::coords_ahead",
-                ahead_pos
-            ),
-        },
-        None => Instruction::LeaveArea,
-    }
-}
-
-#[derive(Hash, Eq, PartialEq, Clone)]
-enum Instruction {
-    Turn,
-    MoveTo { row: usize, col: usize },
-    LeaveArea,
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum Facing {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Facing {
-    fn turn_right(&self) -> Self {
-        match self {
-            Facing::North => Facing::East,
-            Facing::South => Facing::West,
-            Facing::East => Facing::South,
-            Facing::West => Facing::North,
+            RayHit::Exit { .. } => return false,
         }
     }
 }
@@ -140,43 +125,6 @@ impl Facing {
 #[derive(Clone)]
 struct Map(Grid<MapSquare>);
 
-impl Map {
-    /// What is ahead of the position if facing this way? Some coords, or None = leave the area
-    fn coords_ahead(&self, pos: (usize, usize), facing: Facing) -> Option<(usize, usize)> {
-        match facing {
-            Facing::North => {
-                if pos.0 == 0 {
-                    // edge of area
-                    None
-                } else {
-                    Some((pos.0 - 1, pos.1))
-                }
-            }
-            Facing::South => {
-                if pos.0 + 1 == self.0.size().0 {
-                    None
-                } else {
-                    Some((pos.0 + 1, pos.1))
-                }
-            }
-            Facing::East => {
-                if pos.1 + 1 == self.0.size().1 {
-                    None
-                } else {
-                    Some((pos.0, pos.1 + 1))
-                }
-            }
-            Facing::West => {
-                if pos.1 == 0 {
-                    None
-                } else {
-                    Some((pos.0, pos.1 - 1))
-                }
-            }
-        }
-    }
-}
-
 impl TryFrom<&str> for Map {
     type Error = Box<dyn Error>;
 
@@ -197,7 +145,6 @@ impl TryFrom<&str> for Map {
 enum MapSquare {
     Empty,
     Obstacle,
-    Visited,
 }
 
 impl TryFrom<char> for MapSquare {
@@ -232,7 +179,7 @@ fn test_part1() {
 
     assert_eq!(start_pos, (6, 4));
 
-    let visited_count = part1(map, start_pos);
+    let visited_count = part1(&map, start_pos);
     assert_eq!(visited_count, 41);
 }
 
@@ -0,0 +1,279 @@
+use grid::Grid;
+
+use super::Direction;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A fixed-size grid of yes/no flags packed into bits, for remembering which cells have been seen or are blocked
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        BitGrid {
+            rows,
+            cols,
+            words: vec![0; (rows * cols).div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Set the cells of a grid whose values pass the filter
+    #[allow(dead_code)]
+    pub fn from_grid<T>(grid: &Grid<T>, filter: impl Fn(&T) -> bool) -> Self {
+        let mut bits = Self::new(grid.rows(), grid.cols());
+        for (pos, v) in grid.indexed_iter() {
+            if filter(v) {
+                bits.insert(pos);
+            }
+        }
+        bits
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn bit_index(&self, (row, col): (usize, usize)) -> Option<usize> {
+        if row < self.rows && col < self.cols {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// Is the cell set? Cells outside the grid never are.
+    #[allow(dead_code)]
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        self.bit_index(pos)
+            .map(|i| self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Set the cell, returning true if it wasn't already set.
+    /// Panics if the cell is outside the grid.
+    pub fn insert(&mut self, pos: (usize, usize)) -> bool {
+        let i = self
+            .bit_index(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside a grid of size {:?}", pos, self.size()));
+        let word = &mut self.words[i / WORD_BITS];
+        let mask = 1 << (i % WORD_BITS);
+        let was_set = *word & mask != 0;
+        *word |= mask;
+        !was_set
+    }
+
+    /// Unset the cell, returning true if it was set
+    pub fn remove(&mut self, pos: (usize, usize)) -> bool {
+        match self.bit_index(pos) {
+            Some(i) => {
+                let word = &mut self.words[i / WORD_BITS];
+                let mask = 1 << (i % WORD_BITS);
+                let was_set = *word & mask != 0;
+                *word &= !mask;
+                was_set
+            }
+            None => false,
+        }
+    }
+
+    /// Unset every cell, keeping the size
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// How many cells are set
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    #[allow(dead_code)]
+    fn combine_with(&mut self, other: &BitGrid, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.size(),
+            other.size(),
+            "Can only combine bit grids of the same size"
+        );
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w = op(*w, *o);
+        }
+    }
+
+    /// Set every cell which is set in the other grid too
+    #[allow(dead_code)]
+    pub fn union_with(&mut self, other: &BitGrid) {
+        self.combine_with(other, |a, b| a | b);
+    }
+
+    /// Unset every cell which isn't also set in the other grid
+    #[allow(dead_code)]
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        self.combine_with(other, |a, b| a & b);
+    }
+
+    /// Unset every cell which is set in the other grid
+    #[allow(dead_code)]
+    pub fn difference_with(&mut self, other: &BitGrid) {
+        self.combine_with(other, |a, b| a & !b);
+    }
+
+    /// Positions of every set cell in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w != 0)
+            .flat_map(|(wi, w)| {
+                (0..WORD_BITS)
+                    .filter(move |b| w & (1 << b) != 0)
+                    .map(move |b| wi * WORD_BITS + b)
+            })
+            .map(|i| (i / self.cols, i % self.cols))
+    }
+}
+
+fn direction_bit(direction: Direction) -> u8 {
+    let index = match direction {
+        Direction::North => 0,
+        Direction::NorthEast => 1,
+        Direction::East => 2,
+        Direction::SouthEast => 3,
+        Direction::South => 4,
+        Direction::SouthWest => 5,
+        Direction::West => 6,
+        Direction::NorthWest => 7,
+    };
+    1 << index
+}
+
+/// A fixed-size grid remembering which directions each cell has been passed through in.
+/// Seeing the same cell in the same direction twice is the usual sign of going round in a loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectionGrid(Grid<u8>);
+
+impl DirectionGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        DirectionGrid(Grid::new(rows, cols))
+    }
+
+    #[allow(dead_code)]
+    pub fn size(&self) -> (usize, usize) {
+        self.0.size()
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, pos: (usize, usize), direction: Direction) -> bool {
+        self.0
+            .get(pos.0, pos.1)
+            .map(|mask| mask & direction_bit(direction) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Record the direction for the cell, returning true if it hadn't already been recorded.
+    /// Panics if the cell is outside the grid.
+    pub fn insert(&mut self, pos: (usize, usize), direction: Direction) -> bool {
+        let mask = &mut self.0[pos];
+        let was_set = *mask & direction_bit(direction) != 0;
+        *mask |= direction_bit(direction);
+        !was_set
+    }
+
+    /// Every direction recorded for the cell
+    #[allow(dead_code)]
+    pub fn directions(&self, pos: (usize, usize)) -> impl Iterator<Item = Direction> {
+        let mask = self.0.get(pos.0, pos.1).cloned().unwrap_or(0);
+        Direction::ALL
+            .into_iter()
+            .filter(move |d| mask & direction_bit(*d) != 0)
+    }
+
+    /// Forget everything, keeping the size
+    pub fn clear(&mut self) {
+        self.0.fill(0);
+    }
+
+    /// The cells which have been seen in any direction at all
+    #[allow(dead_code)]
+    pub fn cells(&self) -> BitGrid {
+        BitGrid::from_grid(&self.0, |mask| *mask != 0)
+    }
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut bits = BitGrid::new(9, 10);
+    assert!(bits.is_empty());
+    assert!(bits.insert((0, 0)));
+    assert!(!bits.insert((0, 0)));
+    assert!(bits.insert((8, 9)));
+    assert!(bits.insert((6, 4)));
+    assert!(bits.contains((8, 9)));
+    assert!(!bits.contains((8, 8)));
+    assert!(!bits.contains((9, 0)));
+    assert_eq!(bits.count(), 3);
+    assert_eq!(
+        bits.iter().collect::<Vec<_>>(),
+        vec![(0, 0), (6, 4), (8, 9)]
+    );
+
+    assert!(bits.remove((6, 4)));
+    assert!(!bits.remove((6, 4)));
+    assert_eq!(bits.count(), 2);
+
+    bits.clear();
+    assert!(bits.is_empty());
+    assert_eq!(bits.size(), (9, 10));
+}
+
+#[test]
+fn test_set_operations() {
+    let grid = Grid::from_vec((0..20).collect(), 5);
+    let evens = BitGrid::from_grid(&grid, |n| n % 2 == 0);
+    let threes = BitGrid::from_grid(&grid, |n| n % 3 == 0);
+
+    let mut both = evens.clone();
+    both.intersect_with(&threes);
+    assert_eq!(
+        both.iter().collect::<Vec<_>>(),
+        vec![(0, 0), (1, 1), (2, 2), (3, 3)]
+    );
+
+    let mut either = evens.clone();
+    either.union_with(&threes);
+    assert_eq!(either.count(), 13);
+
+    let mut only_evens = evens.clone();
+    only_evens.difference_with(&threes);
+    assert_eq!(only_evens.count(), 6);
+}
+
+#[test]
+fn test_direction_grid() {
+    let mut seen = DirectionGrid::new(3, 3);
+    assert!(seen.insert((1, 1), Direction::North));
+    assert!(seen.insert((1, 1), Direction::West));
+    assert!(!seen.insert((1, 1), Direction::North));
+    assert!(seen.insert((2, 0), Direction::SouthEast));
+    assert!(seen.contains((1, 1), Direction::West));
+    assert!(!seen.contains((1, 1), Direction::East));
+    assert_eq!(
+        seen.directions((1, 1)).collect::<Vec<_>>(),
+        vec![Direction::North, Direction::West]
+    );
+    assert_eq!(
+        seen.cells().iter().collect::<Vec<_>>(),
+        vec![(1, 1), (2, 0)]
+    );
+
+    seen.clear();
+    assert!(seen.cells().is_empty());
+}
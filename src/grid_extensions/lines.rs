@@ -0,0 +1,111 @@
+use grid::Grid;
+
+use super::Direction;
+
+/// A lazy walk across a grid in a straight line, yielding each cell and its value.
+/// Stops at the edge of the grid, or after a set number of cells if limited.
+#[derive(Debug)]
+pub struct Line<'a, T> {
+    grid: &'a Grid<T>,
+    next: Option<(usize, usize)>,
+    direction: Direction,
+    remaining: usize,
+}
+
+impl<'a, T> Line<'a, T> {
+    pub(super) fn new(grid: &'a Grid<T>, start: (usize, usize), direction: Direction) -> Self {
+        let in_grid = start.0 < grid.rows() && start.1 < grid.cols();
+        Line {
+            grid,
+            next: if in_grid { Some(start) } else { None },
+            direction,
+            remaining: usize::MAX,
+        }
+    }
+
+    /// Stop after at most this many cells
+    pub fn limit(mut self, length: usize) -> Self {
+        self.remaining = self.remaining.min(length);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+}
+
+impl<'a, T> Iterator for Line<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let pos = self.next?;
+        self.remaining -= 1;
+        self.next = self.direction.step(pos, self.grid.size());
+        Some((pos, &self.grid[pos]))
+    }
+}
+
+#[cfg(test)]
+use super::GridExtensions;
+
+#[test]
+fn test_lines_clipped_at_edge() {
+    let grid = Grid::from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], 3);
+
+    // every line from a corner contains the corner, but five of them stop straight away
+    let lines = grid.lines_from(0, 0, 3);
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines.iter().filter(|l| l.len() == 1).count(), 5);
+    assert_eq!(lines.iter().filter(|l| l.len() == 3).count(), 3);
+
+    let full = grid.full_lines_from(0, 0, 3);
+    assert_eq!(full.len(), 3);
+    assert!(full.iter().all(|l| l.len() == 3));
+
+    // lines can be shorter than asked for but still not reach the edge
+    assert_eq!(grid.full_lines_from(1, 1, 2).len(), 8);
+    assert!(grid.full_lines_from(1, 1, 3).is_empty());
+
+    assert!(grid.lines_from(3, 0, 3).is_empty());
+    assert!(grid.lines_from(0, 0, 0).is_empty());
+}
+
+#[test]
+fn test_directed_lines() {
+    let grid = Grid::from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], 3);
+    let lines = grid.directed_lines_from(1, 1, 3);
+    let values = |d: Direction| {
+        lines
+            .iter()
+            .find(|(ld, _)| *ld == d)
+            .map(|(_, l)| l.iter().map(|(_, v)| **v).collect::<Vec<_>>())
+            .unwrap()
+    };
+    assert_eq!(values(Direction::North), vec![5, 2]);
+    assert_eq!(values(Direction::SouthEast), vec![5, 9]);
+    assert_eq!(values(Direction::West), vec![5, 4]);
+
+    let lazy = grid
+        .iter_lines_from(1, 1, 3)
+        .map(|(d, l)| (d, l.collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    assert_eq!(lazy, lines);
+}
+
+#[test]
+fn test_line_from() {
+    let grid = Grid::from_vec((0..12).collect(), 4);
+    let line = grid.line_from(2, 0, Direction::NorthEast);
+    assert_eq!(line.direction(), Direction::NorthEast);
+    assert_eq!(
+        line.map(|(p, _)| p).collect::<Vec<_>>(),
+        vec![(2, 0), (1, 1), (0, 2)]
+    );
+    assert_eq!(grid.line_from(0, 0, Direction::East).limit(2).count(), 2);
+    assert_eq!(grid.line_from(0, 4, Direction::West).count(), 0);
+}
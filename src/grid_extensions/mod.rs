@@ -0,0 +1,506 @@
+// Toolkit for the grid days. Pieces no day uses yet are marked `#[allow(dead_code)]` one at a time, so
+// anything else that ends up unused still gets a warning.
+
+use grid::Grid;
+use sparse_grid::Point;
+
+pub mod bit_grid;
+mod lines;
+mod patterns;
+pub mod rays;
+mod regions;
+pub mod sparse_grid;
+mod transform;
+
+pub use lines::Line;
+pub use patterns::{Orientation, Stencil, StencilMatch, WordMatch};
+pub use rays::RayHit;
+pub use regions::Regions;
+pub use transform::GridView;
+
+/// One of the eight compass directions, with north being towards row 0 and west towards column 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    #[allow(dead_code)]
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The (row, col) change from taking one step in this direction
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+
+    /// Take one step from the position in this direction, or None if that would leave a grid of the given size
+    pub fn step(
+        &self,
+        (row, col): (usize, usize),
+        (rows, cols): (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let (d_row, d_col) = self.offset();
+        let row = row.checked_add_signed(d_row)?;
+        let col = col.checked_add_signed(d_col)?;
+        if row < rows && col < cols {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Take one step from the point in this direction, with no edges to worry about
+    #[allow(dead_code)]
+    pub fn step_unbounded(&self, (row, col): Point) -> Point {
+        let (d_row, d_col) = self.offset();
+        (row + d_row as i64, col + d_col as i64)
+    }
+
+    pub fn turn_right(&self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::NorthEast => Direction::SouthEast,
+            Direction::East => Direction::South,
+            Direction::SouthEast => Direction::SouthWest,
+            Direction::South => Direction::West,
+            Direction::SouthWest => Direction::NorthWest,
+            Direction::West => Direction::North,
+            Direction::NorthWest => Direction::NorthEast,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn turn_left(&self) -> Self {
+        self.opposite().turn_right()
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.turn_right().turn_right()
+    }
+}
+
+/// The cells of a line collected up, each with its position
+#[allow(dead_code)]
+pub type LineCells<'a, T> = Vec<((usize, usize), &'a T)>;
+
+pub trait GridExtensions<T> {
+    #[allow(dead_code)]
+    fn surrounding(&self, row: usize, col: usize) -> Vec<(usize, usize)>;
+
+    /// Up to eight straight lines of up to `count` cells starting at the given cell, one in each direction.
+    /// Lines are clipped where they meet the edge of the grid, so near the edge some will be shorter than `count`,
+    /// but each line always includes the starting cell. A starting cell outside the grid gives no lines at all.
+    #[allow(dead_code)]
+    fn lines_from(&self, row: usize, col: usize, count: usize) -> Vec<Vec<((usize, usize), &T)>>;
+
+    /// As `lines_from`, but leaving out any line which was clipped by the edge of the grid
+    #[allow(dead_code)]
+    fn full_lines_from(
+        &self,
+        row: usize,
+        col: usize,
+        count: usize,
+    ) -> Vec<Vec<((usize, usize), &T)>>;
+
+    /// As `lines_from`, but with each line tagged with the direction it runs in
+    #[allow(dead_code)]
+    fn directed_lines_from(
+        &self,
+        row: usize,
+        col: usize,
+        count: usize,
+    ) -> Vec<(Direction, LineCells<'_, T>)>;
+
+    /// As `directed_lines_from`, but lazily, without collecting any of the lines
+    #[allow(dead_code)]
+    fn iter_lines_from<'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        count: usize,
+    ) -> impl Iterator<Item = (Direction, Line<'a, T>)>
+    where
+        T: 'a;
+
+    /// Every cell in a straight line from the given cell up to the edge of the grid, starting with the given cell
+    fn line_from(&self, row: usize, col: usize, direction: Direction) -> Line<'_, T>;
+
+    /// Look along a straight line from the given cell, not including the cell itself, for the first cell
+    /// whose value matches the predicate
+    fn cast_ray(
+        &self,
+        row: usize,
+        col: usize,
+        direction: Direction,
+        predicate: impl Fn(&T) -> bool,
+    ) -> RayHit;
+
+    /// The up to four cells which share an edge with the given one, in `Direction::ORTHOGONAL` order
+    #[allow(dead_code)]
+    fn orthogonal_neighbours(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (Direction, (usize, usize))>;
+
+    /// Every cell connected to the given one through orthogonal neighbours holding an equal value, including itself
+    #[allow(dead_code)]
+    fn flood_fill(&self, row: usize, col: usize) -> Vec<(usize, usize)>
+    where
+        T: PartialEq;
+
+    /// Split the whole grid into connected regions of equal values
+    #[allow(dead_code)]
+    fn regions(&self) -> Regions
+    where
+        T: PartialEq;
+
+    /// Every place the word can be read in a straight line in any of the eight directions.
+    /// Words that read the same backwards are only reported once, going whichever way comes first in `Direction::ALL`.
+    fn find_word(&self, word: &[T]) -> Vec<WordMatch>
+    where
+        T: PartialEq;
+
+    /// Every place the stencil fits in any of the given orientations.
+    /// Orientations which make the stencil look the same as an earlier one are skipped, so each fit is only reported once.
+    #[allow(dead_code)]
+    fn find_stencil(&self, stencil: &Stencil<T>, orientations: &[Orientation]) -> Vec<StencilMatch>
+    where
+        T: Clone + PartialEq;
+
+    /// Does the stencil, in the given orientation, fit with its top left corner at this position?
+    fn stencil_matches_at(
+        &self,
+        stencil: &Stencil<T>,
+        orientation: Orientation,
+        top_left: (usize, usize),
+    ) -> bool
+    where
+        T: Clone + PartialEq;
+
+    /// A copy of the grid in the given orientation
+    #[allow(dead_code)]
+    fn oriented(&self, orientation: Orientation) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A copy of the grid turned a quarter turn clockwise
+    #[allow(dead_code)]
+    fn rotated_90(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    #[allow(dead_code)]
+    fn rotated_180(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A copy of the grid turned a quarter turn anticlockwise
+    #[allow(dead_code)]
+    fn rotated_270(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A copy of the grid with rows and columns swapped
+    #[allow(dead_code)]
+    fn transposed(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A copy of the grid mirrored left to right
+    #[allow(dead_code)]
+    fn flipped_horizontal(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A copy of the grid mirrored top to bottom
+    #[allow(dead_code)]
+    fn flipped_vertical(&self) -> Grid<T>
+    where
+        T: Clone;
+
+    /// A borrowed window onto part of the grid, or None if it doesn't fit
+    #[allow(dead_code)]
+    fn view(&self, top_left: (usize, usize), size: (usize, usize)) -> Option<GridView<'_, T>>;
+
+    /// A copy of part of the grid, or None if it doesn't fit
+    #[allow(dead_code)]
+    fn cropped(&self, top_left: (usize, usize), size: (usize, usize)) -> Option<Grid<T>>
+    where
+        T: Clone;
+
+    /// The grid repeated `down` times vertically and `across` times horizontally
+    #[allow(dead_code)]
+    fn tiled(&self, down: usize, across: usize) -> Grid<T>
+    where
+        T: Clone;
+}
+
+impl<T> GridExtensions<T> for Grid<T> {
+    fn surrounding(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut surrounds = Vec::new();
+
+        if row >= self.size().0 || col >= self.size().1 {
+            return surrounds;
+        }
+
+        let x_below = row < self.size().0 - 1;
+        let y_below = col < self.size().1 - 1;
+        let x_above = row > 0;
+        let y_above = col > 0;
+
+        if x_below {
+            surrounds.push((row + 1, col));
+        }
+        if y_below {
+            surrounds.push((row, col + 1));
+        }
+        if x_above {
+            surrounds.push((row - 1, col));
+        }
+        if y_above {
+            surrounds.push((row, col - 1));
+        }
+        if x_below && y_below {
+            surrounds.push((row + 1, col + 1));
+        }
+        if x_above && y_above {
+            surrounds.push((row - 1, col - 1));
+        }
+        if x_below && y_above {
+            surrounds.push((row + 1, col - 1));
+        }
+        if x_above && y_below {
+            surrounds.push((row - 1, col + 1));
+        }
+
+        surrounds
+    }
+
+    fn lines_from(&self, row: usize, col: usize, length: usize) -> Vec<Vec<((usize, usize), &T)>> {
+        self.iter_lines_from(row, col, length)
+            .map(|(_, line)| line.collect())
+            .collect()
+    }
+
+    fn full_lines_from(
+        &self,
+        row: usize,
+        col: usize,
+        length: usize,
+    ) -> Vec<Vec<((usize, usize), &T)>> {
+        self.iter_lines_from(row, col, length)
+            .map(|(_, line)| line.collect::<Vec<_>>())
+            .filter(|line| line.len() == length)
+            .collect()
+    }
+
+    fn directed_lines_from(
+        &self,
+        row: usize,
+        col: usize,
+        length: usize,
+    ) -> Vec<(Direction, LineCells<'_, T>)> {
+        self.iter_lines_from(row, col, length)
+            .map(|(d, line)| (d, line.collect()))
+            .collect()
+    }
+
+    fn iter_lines_from<'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        length: usize,
+    ) -> impl Iterator<Item = (Direction, Line<'a, T>)>
+    where
+        T: 'a,
+    {
+        let in_grid = row < self.rows() && col < self.cols();
+        Direction::ALL
+            .into_iter()
+            .filter(move |_| in_grid && length > 0)
+            .map(move |d| (d, self.line_from(row, col, d).limit(length)))
+    }
+
+    fn line_from(&self, row: usize, col: usize, direction: Direction) -> Line<'_, T> {
+        Line::new(self, (row, col), direction)
+    }
+
+    fn cast_ray(
+        &self,
+        row: usize,
+        col: usize,
+        direction: Direction,
+        predicate: impl Fn(&T) -> bool,
+    ) -> RayHit {
+        rays::cast_ray(self, (row, col), direction, predicate)
+    }
+
+    fn orthogonal_neighbours(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (Direction, (usize, usize))> {
+        let size = self.size();
+        let in_grid = row < size.0 && col < size.1;
+        Direction::ORTHOGONAL
+            .into_iter()
+            .filter(move |_| in_grid)
+            .filter_map(move |d| d.step((row, col), size).map(|p| (d, p)))
+    }
+
+    fn flood_fill(&self, row: usize, col: usize) -> Vec<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        regions::flood_fill(self, (row, col))
+    }
+
+    fn regions(&self) -> Regions
+    where
+        T: PartialEq,
+    {
+        regions::label_regions(self)
+    }
+
+    fn find_word(&self, word: &[T]) -> Vec<WordMatch>
+    where
+        T: PartialEq,
+    {
+        patterns::find_word(self, word)
+    }
+
+    fn find_stencil(&self, stencil: &Stencil<T>, orientations: &[Orientation]) -> Vec<StencilMatch>
+    where
+        T: Clone + PartialEq,
+    {
+        patterns::find_stencil(self, stencil, orientations)
+    }
+
+    fn stencil_matches_at(
+        &self,
+        stencil: &Stencil<T>,
+        orientation: Orientation,
+        top_left: (usize, usize),
+    ) -> bool
+    where
+        T: Clone + PartialEq,
+    {
+        patterns::stencil_at(self, &orientation.apply(stencil), top_left)
+    }
+
+    fn oriented(&self, orientation: Orientation) -> Grid<T>
+    where
+        T: Clone,
+    {
+        orientation.apply(self)
+    }
+
+    fn rotated_90(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::Rotate90)
+    }
+
+    fn rotated_180(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::Rotate180)
+    }
+
+    fn rotated_270(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::Rotate270)
+    }
+
+    fn transposed(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::Transpose)
+    }
+
+    fn flipped_horizontal(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::FlipHorizontal)
+    }
+
+    fn flipped_vertical(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.oriented(Orientation::FlipVertical)
+    }
+
+    fn view(&self, top_left: (usize, usize), size: (usize, usize)) -> Option<GridView<'_, T>> {
+        GridView::new(self, top_left, size)
+    }
+
+    fn cropped(&self, top_left: (usize, usize), size: (usize, usize)) -> Option<Grid<T>>
+    where
+        T: Clone,
+    {
+        self.view(top_left, size).map(|v| v.to_grid())
+    }
+
+    fn tiled(&self, down: usize, across: usize) -> Grid<T>
+    where
+        T: Clone,
+    {
+        transform::tile(self, down, across)
+    }
+}
+
+#[cfg(test)]
+fn char_grid(input: &str) -> Grid<char> {
+    let cols = input.lines().next().map(|l| l.len()).unwrap_or(0);
+    Grid::from_vec(input.lines().flat_map(|l| l.chars()).collect(), cols)
+}
+
+#[test]
+fn test_lines_from() {
+    let grid = Grid::from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], 3);
+    let lines = grid.lines_from(1, 1, 2);
+    assert_eq!(lines.len(), 8);
+    assert!(lines.iter().all(|l| l[0].0 == (1, 1)));
+    assert!(lines.iter().all(|l| l.len() == 2));
+    println!("{:?}", lines);
+}
@@ -0,0 +1,382 @@
+use grid::Grid;
+
+use super::{Direction, GridExtensions};
+
+/// One of the eight ways a rectangle can be rotated and reflected onto itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Identity,
+    /// Rotated a quarter turn clockwise
+    Rotate90,
+    Rotate180,
+    /// Rotated a quarter turn anticlockwise
+    Rotate270,
+    /// Mirrored left to right
+    #[allow(dead_code)]
+    FlipHorizontal,
+    /// Mirrored top to bottom
+    #[allow(dead_code)]
+    FlipVertical,
+    /// Mirrored along the top-left to bottom-right diagonal
+    #[allow(dead_code)]
+    Transpose,
+    /// Mirrored along the top-right to bottom-left diagonal
+    #[allow(dead_code)]
+    AntiTranspose,
+}
+
+impl Orientation {
+    #[allow(dead_code)]
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::FlipHorizontal,
+        Orientation::FlipVertical,
+        Orientation::Transpose,
+        Orientation::AntiTranspose,
+    ];
+
+    pub const ROTATIONS: [Orientation; 4] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+    ];
+
+    fn swaps_axes(&self) -> bool {
+        matches!(
+            self,
+            Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::Transpose
+                | Orientation::AntiTranspose
+        )
+    }
+
+    /// Size of a grid of the given size once it has been put in this orientation
+    pub fn apply_to_size(&self, (rows, cols): (usize, usize)) -> (usize, usize) {
+        if self.swaps_axes() {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        }
+    }
+
+    /// Where in the original grid of the given size the cell at `(row, col)` of the oriented grid comes from
+    fn source_of(
+        &self,
+        (row, col): (usize, usize),
+        (rows, cols): (usize, usize),
+    ) -> (usize, usize) {
+        match self {
+            Orientation::Identity => (row, col),
+            Orientation::Rotate90 => (rows - 1 - col, row),
+            Orientation::Rotate180 => (rows - 1 - row, cols - 1 - col),
+            Orientation::Rotate270 => (col, cols - 1 - row),
+            Orientation::FlipHorizontal => (row, cols - 1 - col),
+            Orientation::FlipVertical => (rows - 1 - row, col),
+            Orientation::Transpose => (col, row),
+            Orientation::AntiTranspose => (rows - 1 - col, cols - 1 - row),
+        }
+    }
+
+    /// Make a copy of the grid in this orientation
+    pub fn apply<T: Clone>(&self, grid: &Grid<T>) -> Grid<T> {
+        let size = grid.size();
+        let (rows, cols) = self.apply_to_size(size);
+        let mut cells = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                cells.push(grid[self.source_of((row, col), size)].clone());
+            }
+        }
+        Grid::from_vec(cells, cols)
+    }
+}
+
+/// A word found in a grid, reading from `start` in `direction`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WordMatch {
+    pub start: (usize, usize),
+    pub direction: Direction,
+}
+
+/// A stencil found in a grid. `top_left` is where the top left of the stencil sits once it's been put in `orientation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(dead_code)]
+pub struct StencilMatch {
+    pub top_left: (usize, usize),
+    pub orientation: Orientation,
+}
+
+/// A 2D pattern where `None` cells match anything
+pub type Stencil<T> = Grid<Option<T>>;
+
+pub(super) fn word_at<T: PartialEq>(
+    grid: &Grid<T>,
+    word: &[T],
+    start: (usize, usize),
+    direction: Direction,
+) -> bool {
+    grid.line_from(start.0, start.1, direction)
+        .limit(word.len())
+        .map(|(_, v)| v)
+        .eq(word.iter())
+}
+
+pub(super) fn find_word<T: PartialEq>(grid: &Grid<T>, word: &[T]) -> Vec<WordMatch> {
+    let Some(first) = word.first() else {
+        return Vec::new();
+    };
+
+    // a single letter reads the same in every direction, so only report it once, and a palindrome reads the
+    // same from either end, so only look one way along each line. The first four directions don't include
+    // any pair of opposites.
+    let directions: &[Direction] = if word.len() == 1 {
+        &Direction::ALL[..1]
+    } else if word.iter().eq(word.iter().rev()) {
+        &Direction::ALL[..4]
+    } else {
+        &Direction::ALL
+    };
+
+    grid.indexed_iter()
+        .filter(|(_, v)| *v == first)
+        .flat_map(|(start, _)| {
+            directions
+                .iter()
+                .filter(move |d| word_at(grid, word, start, **d))
+                .map(move |d| WordMatch {
+                    start,
+                    direction: *d,
+                })
+        })
+        .collect()
+}
+
+/// The stencil in each of the requested orientations, skipping any which look identical to one already produced
+#[allow(dead_code)]
+fn oriented_stencils<T: Clone + PartialEq>(
+    stencil: &Stencil<T>,
+    orientations: &[Orientation],
+) -> Vec<(Orientation, Stencil<T>)> {
+    let mut oriented: Vec<(Orientation, Stencil<T>)> = Vec::new();
+    for o in orientations {
+        let candidate = o.apply(stencil);
+        if !oriented.iter().any(|(_, s)| *s == candidate) {
+            oriented.push((*o, candidate));
+        }
+    }
+    oriented
+}
+
+pub(super) fn stencil_at<T: PartialEq>(
+    grid: &Grid<T>,
+    stencil: &Stencil<T>,
+    (top, left): (usize, usize),
+) -> bool {
+    if top + stencil.rows() > grid.rows() || left + stencil.cols() > grid.cols() {
+        return false;
+    }
+
+    stencil.indexed_iter().all(|((row, col), s)| match s {
+        Some(s) => grid[(top + row, left + col)] == *s,
+        None => true,
+    })
+}
+
+#[allow(dead_code)]
+pub(super) fn find_stencil<T: Clone + PartialEq>(
+    grid: &Grid<T>,
+    stencil: &Stencil<T>,
+    orientations: &[Orientation],
+) -> Vec<StencilMatch> {
+    let mut found = Vec::new();
+
+    for (orientation, oriented) in oriented_stencils(stencil, orientations) {
+        if oriented.rows() > grid.rows() || oriented.cols() > grid.cols() {
+            continue;
+        }
+
+        for top in 0..=(grid.rows() - oriented.rows()) {
+            for left in 0..=(grid.cols() - oriented.cols()) {
+                if stencil_at(grid, &oriented, (top, left)) {
+                    found.push(StencilMatch {
+                        top_left: (top, left),
+                        orientation,
+                    });
+                }
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+use super::char_grid;
+
+#[cfg(test)]
+fn stencil(input: &str) -> Stencil<char> {
+    let grid = char_grid(input);
+    Grid::from_vec(
+        grid.iter()
+            .map(|c| if *c == '.' { None } else { Some(*c) })
+            .collect(),
+        grid.cols(),
+    )
+}
+
+#[test]
+fn test_orientation_apply() {
+    let grid = Grid::from_vec(vec![1, 2, 3, 4, 5, 6], 3);
+    assert_eq!(
+        Orientation::Rotate90.apply(&grid),
+        Grid::from_vec(vec![4, 1, 5, 2, 6, 3], 2)
+    );
+    assert_eq!(
+        Orientation::Rotate180.apply(&grid),
+        Grid::from_vec(vec![6, 5, 4, 3, 2, 1], 3)
+    );
+    assert_eq!(
+        Orientation::Rotate270.apply(&grid),
+        Grid::from_vec(vec![3, 6, 2, 5, 1, 4], 2)
+    );
+    assert_eq!(
+        Orientation::FlipHorizontal.apply(&grid),
+        Grid::from_vec(vec![3, 2, 1, 6, 5, 4], 3)
+    );
+    assert_eq!(
+        Orientation::FlipVertical.apply(&grid),
+        Grid::from_vec(vec![4, 5, 6, 1, 2, 3], 3)
+    );
+    assert_eq!(
+        Orientation::Transpose.apply(&grid),
+        Grid::from_vec(vec![1, 4, 2, 5, 3, 6], 2)
+    );
+    assert_eq!(
+        Orientation::AntiTranspose.apply(&grid),
+        Grid::from_vec(vec![6, 3, 5, 2, 4, 1], 2)
+    );
+}
+
+#[test]
+fn test_find_word() {
+    let grid = char_grid(
+        "..X...
+.SAMX.
+.A..A.
+XMAS.S
+.X....",
+    );
+    let word = ['X', 'M', 'A', 'S'];
+    let mut found = grid.find_word(&word);
+    found.sort_by_key(|m| m.start);
+    assert_eq!(
+        found,
+        vec![
+            WordMatch {
+                start: (0, 2),
+                direction: Direction::SouthEast
+            },
+            WordMatch {
+                start: (1, 4),
+                direction: Direction::West
+            },
+            WordMatch {
+                start: (3, 0),
+                direction: Direction::East
+            },
+            WordMatch {
+                start: (4, 1),
+                direction: Direction::North
+            },
+        ]
+    );
+
+    assert_eq!(grid.find_word(&['M']).len(), 2);
+    assert!(grid.find_word(&[]).is_empty());
+}
+
+#[test]
+fn test_find_palindrome() {
+    let grid = char_grid(
+        "ABA.
+B.B.
+ABAB",
+    );
+    let mut found = grid.find_word(&['A', 'B', 'A']);
+    found.sort_by_key(|m| m.start);
+    assert_eq!(
+        found,
+        vec![
+            WordMatch {
+                start: (0, 0),
+                direction: Direction::East
+            },
+            WordMatch {
+                start: (2, 0),
+                direction: Direction::North
+            },
+            WordMatch {
+                start: (2, 0),
+                direction: Direction::East
+            },
+            WordMatch {
+                start: (2, 2),
+                direction: Direction::North
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_find_stencil_all_orientations() {
+    let grid = char_grid(
+        "M.SS.S
+.A..A.
+M.SM.M",
+    );
+    let x_mas = stencil(
+        "M.S
+.A.
+M.S",
+    );
+
+    // the stencil is symmetric top to bottom, so only four of the eight orientations are distinct
+    assert_eq!(oriented_stencils(&x_mas, &Orientation::ALL).len(), 4);
+
+    let found = grid.find_stencil(&x_mas, &Orientation::ALL);
+    assert_eq!(
+        found,
+        vec![
+            StencilMatch {
+                top_left: (0, 0),
+                orientation: Orientation::Identity
+            },
+            StencilMatch {
+                top_left: (0, 3),
+                orientation: Orientation::Rotate270
+            },
+        ]
+    );
+
+    assert_eq!(grid.find_stencil(&x_mas, &[Orientation::Identity]).len(), 1);
+}
+
+#[test]
+fn test_find_stencil_too_big() {
+    let grid = char_grid("AB");
+    assert!(grid
+        .find_stencil(&stencil("A\nB"), &[Orientation::Identity])
+        .is_empty());
+    assert_eq!(
+        grid.find_stencil(&stencil("A\nB"), &Orientation::ROTATIONS),
+        vec![StencilMatch {
+            top_left: (0, 0),
+            orientation: Orientation::Rotate270
+        }]
+    );
+}
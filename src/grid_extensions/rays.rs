@@ -0,0 +1,154 @@
+use grid::Grid;
+
+use super::{sparse_grid::Point, Direction, GridExtensions};
+
+/// Where a ray cast across a grid ended up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RayHit {
+    /// Found a matching cell `distance` steps away
+    Found {
+        pos: (usize, usize),
+        distance: usize,
+    },
+    /// Reached the edge of the grid without finding anything. `last` is the last cell inside the grid,
+    /// which is `distance` steps away and may be the starting cell itself.
+    Exit {
+        last: (usize, usize),
+        distance: usize,
+    },
+}
+
+pub(super) fn cast_ray<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    direction: Direction,
+    predicate: impl Fn(&T) -> bool,
+) -> RayHit {
+    let mut last = start;
+    let mut distance = 0;
+    for (pos, v) in grid.line_from(start.0, start.1, direction).skip(1) {
+        distance += 1;
+        if predicate(v) {
+            return RayHit::Found { pos, distance };
+        }
+        last = pos;
+    }
+    RayHit::Exit { last, distance }
+}
+
+#[allow(dead_code)]
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The cells a straight line between two points passes through, by Bresenham's algorithm.
+/// Includes both ends, and every step moves to one of the eight surrounding cells.
+#[allow(dead_code)]
+pub fn bresenham_line(from: Point, to: Point) -> Vec<Point> {
+    let d_row = (to.0 - from.0).abs();
+    let d_col = -(to.1 - from.1).abs();
+    let step_row = if from.0 < to.0 { 1 } else { -1 };
+    let step_col = if from.1 < to.1 { 1 } else { -1 };
+
+    let mut error = d_row + d_col;
+    let mut pos = from;
+    let mut line = vec![pos];
+
+    while pos != to {
+        let doubled = 2 * error;
+        if doubled >= d_col {
+            error += d_col;
+            pos.0 += step_row;
+        }
+        if doubled <= d_row {
+            error += d_row;
+            pos.1 += step_col;
+        }
+        line.push(pos);
+    }
+
+    line
+}
+
+/// The points with whole-number coordinates lying exactly on the line between two points, including both ends
+#[allow(dead_code)]
+pub fn lattice_points_between(from: Point, to: Point) -> Vec<Point> {
+    let (d_row, d_col) = (to.0 - from.0, to.1 - from.1);
+    let steps = gcd(d_row, d_col);
+    if steps == 0 {
+        return vec![from];
+    }
+
+    let (step_row, step_col) = (d_row / steps, d_col / steps);
+    (0..=steps)
+        .map(|i| (from.0 + i * step_row, from.1 + i * step_col))
+        .collect()
+}
+
+#[test]
+fn test_cast_ray() {
+    let grid = Grid::from_vec("....#.....#.#...".chars().collect(), 4);
+    // the starting cell doesn't count even if it matches
+    assert_eq!(
+        grid.cast_ray(3, 0, Direction::North, |c| *c == '#'),
+        RayHit::Found {
+            pos: (1, 0),
+            distance: 2
+        }
+    );
+    assert_eq!(
+        grid.cast_ray(0, 0, Direction::East, |c| *c == '#'),
+        RayHit::Exit {
+            last: (0, 3),
+            distance: 3
+        }
+    );
+    assert_eq!(
+        grid.cast_ray(0, 3, Direction::North, |c| *c == '#'),
+        RayHit::Exit {
+            last: (0, 3),
+            distance: 0
+        }
+    );
+    assert_eq!(
+        grid.cast_ray(0, 0, Direction::SouthEast, |c| *c == '#'),
+        RayHit::Found {
+            pos: (2, 2),
+            distance: 2
+        }
+    );
+}
+
+#[test]
+fn test_bresenham_line() {
+    assert_eq!(
+        bresenham_line((0, 0), (2, 4)),
+        vec![(0, 0), (1, 1), (1, 2), (2, 3), (2, 4)]
+    );
+    assert_eq!(
+        bresenham_line((3, 1), (0, 1)),
+        vec![(3, 1), (2, 1), (1, 1), (0, 1)]
+    );
+    assert_eq!(bresenham_line((-2, -2), (-2, -2)), vec![(-2, -2)]);
+
+    let line = bresenham_line((5, -3), (-4, 7));
+    assert_eq!(line.first(), Some(&(5, -3)));
+    assert_eq!(line.last(), Some(&(-4, 7)));
+    assert!(line
+        .windows(2)
+        .all(|w| (w[0].0 - w[1].0).abs() <= 1 && (w[0].1 - w[1].1).abs() <= 1));
+}
+
+#[test]
+fn test_lattice_points_between() {
+    assert_eq!(
+        lattice_points_between((1, 8), (7, -1)),
+        vec![(1, 8), (3, 5), (5, 2), (7, -1)]
+    );
+    assert_eq!(lattice_points_between((0, 0), (2, 3)), vec![(0, 0), (2, 3)]);
+    assert_eq!(lattice_points_between((4, 4), (4, 4)), vec![(4, 4)]);
+}
@@ -0,0 +1,257 @@
+use grid::Grid;
+
+use super::{bit_grid::BitGrid, Direction, GridExtensions};
+
+/// The result of splitting a grid into connected regions of equal values
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct Regions {
+    /// For each cell of the original grid, the label of the region it belongs to (an index into `regions`)
+    pub labels: Grid<usize>,
+    pub regions: Vec<Region>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Region {
+    pub label: usize,
+    /// The first cell of the region in row-major order, handy for looking up the region's value
+    pub origin: (usize, usize),
+    pub area: usize,
+    /// Number of cell edges which border a different region or the edge of the grid
+    pub perimeter: usize,
+    /// Number of straight sides of the region's outline, including those of any holes.
+    /// A polygon has as many corners as it has sides, so this is also the number of corners.
+    pub sides: usize,
+    /// Top left corner of the bounding box, inclusive
+    pub top_left: (usize, usize),
+    /// Bottom right corner of the bounding box, inclusive
+    pub bottom_right: (usize, usize),
+}
+
+#[allow(dead_code)]
+impl Regions {
+    pub fn region_at(&self, row: usize, col: usize) -> Option<&Region> {
+        self.labels.get(row, col).map(|l| &self.regions[*l])
+    }
+}
+
+#[allow(dead_code)]
+pub(super) fn flood_fill<T: PartialEq>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut seen = BitGrid::new(grid.rows(), grid.cols());
+    fill(grid, start, |pos| seen.insert(pos))
+}
+
+/// Every cell connected to `start` with the same value, visiting only cells that `claim` hasn't been called on
+/// before. `claim` marks the cell as visited, returning false if it already was.
+fn fill<T: PartialEq>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    mut claim: impl FnMut((usize, usize)) -> bool,
+) -> Vec<(usize, usize)> {
+    let Some(value) = grid.get(start.0, start.1) else {
+        return Vec::new();
+    };
+    if !claim(start) {
+        return Vec::new();
+    }
+
+    let mut to_visit = vec![start];
+    let mut filled = Vec::new();
+
+    while let Some(pos) = to_visit.pop() {
+        filled.push(pos);
+        for (_, n) in grid.orthogonal_neighbours(pos.0, pos.1) {
+            if grid[n] == *value && claim(n) {
+                to_visit.push(n);
+            }
+        }
+    }
+
+    filled
+}
+
+/// Labels every region with one pass over the grid, the labels themselves keeping track of which cells
+/// have been visited
+#[allow(dead_code)]
+pub(super) fn label_regions<T: PartialEq>(grid: &Grid<T>) -> Regions {
+    let mut labels: Grid<Option<usize>> = Grid::init(grid.rows(), grid.cols(), None);
+    let mut cells_by_region = Vec::new();
+
+    for ((row, col), _) in grid.indexed_iter() {
+        if labels[(row, col)].is_some() {
+            continue;
+        }
+
+        let label = cells_by_region.len();
+        let cells = fill(grid, (row, col), |pos| {
+            if labels[pos].is_some() {
+                false
+            } else {
+                labels[pos] = Some(label);
+                true
+            }
+        });
+        cells_by_region.push(cells);
+    }
+
+    let labels = Grid::from_vec(
+        labels
+            .into_vec()
+            .into_iter()
+            .map(|l| l.expect("flood fill should have labelled every cell"))
+            .collect(),
+        grid.cols(),
+    );
+
+    let regions = cells_by_region
+        .into_iter()
+        .enumerate()
+        .map(|(label, cells)| measure_region(&labels, label, &cells))
+        .collect();
+
+    Regions { labels, regions }
+}
+
+#[allow(dead_code)]
+fn measure_region(labels: &Grid<usize>, label: usize, cells: &[(usize, usize)]) -> Region {
+    let size = labels.size();
+    let same = |pos: (usize, usize), d: Direction| {
+        d.step(pos, size)
+            .map(|(r, c)| labels[(r, c)] == label)
+            .unwrap_or(false)
+    };
+
+    let mut perimeter = 0;
+    let mut sides = 0;
+
+    for pos in cells.iter().cloned() {
+        perimeter += Direction::ORTHOGONAL
+            .into_iter()
+            .filter(|d| !same(pos, *d))
+            .count();
+
+        // count corners at each of the cell's four vertices
+        // outside corners have both edges open, inside corners have both edges closed but the diagonal open
+        for (a, b, diagonal) in [
+            (Direction::North, Direction::East, Direction::NorthEast),
+            (Direction::East, Direction::South, Direction::SouthEast),
+            (Direction::South, Direction::West, Direction::SouthWest),
+            (Direction::West, Direction::North, Direction::NorthWest),
+        ] {
+            let (a, b, diagonal) = (same(pos, a), same(pos, b), same(pos, diagonal));
+            if (!a && !b) || (a && b && !diagonal) {
+                sides += 1;
+            }
+        }
+    }
+
+    Region {
+        label,
+        origin: cells.iter().min().cloned().unwrap_or_default(),
+        area: cells.len(),
+        perimeter,
+        sides,
+        top_left: (
+            cells.iter().map(|p| p.0).min().unwrap_or_default(),
+            cells.iter().map(|p| p.1).min().unwrap_or_default(),
+        ),
+        bottom_right: (
+            cells.iter().map(|p| p.0).max().unwrap_or_default(),
+            cells.iter().map(|p| p.1).max().unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(test)]
+use super::char_grid;
+
+#[test]
+fn test_flood_fill() {
+    let grid = char_grid(
+        "AAB
+ABB
+CCB",
+    );
+    let mut filled = grid.flood_fill(0, 0);
+    filled.sort();
+    assert_eq!(filled, vec![(0, 0), (0, 1), (1, 0)]);
+    assert_eq!(grid.flood_fill(1, 2).len(), 4);
+    assert!(grid.flood_fill(3, 0).is_empty());
+}
+
+#[test]
+fn test_regions_simple() {
+    let grid = char_grid(
+        "AAAA
+BBCD
+BBCC
+EEEC",
+    );
+    let regions = grid.regions();
+    assert_eq!(regions.regions.len(), 5);
+
+    let a = regions.region_at(0, 0).unwrap();
+    assert_eq!((a.area, a.perimeter, a.sides), (4, 10, 4));
+    assert_eq!((a.top_left, a.bottom_right), ((0, 0), (0, 3)));
+
+    let c = regions.region_at(1, 2).unwrap();
+    assert_eq!((c.area, c.perimeter, c.sides), (4, 10, 8));
+    assert_eq!((c.top_left, c.bottom_right), ((1, 2), (3, 3)));
+
+    let d = regions.region_at(1, 3).unwrap();
+    assert_eq!((d.area, d.perimeter, d.sides), (1, 4, 4));
+
+    assert_eq!(regions.labels[(2, 1)], regions.labels[(1, 0)]);
+    assert_ne!(regions.labels[(2, 1)], regions.labels[(2, 2)]);
+}
+
+#[test]
+fn test_regions_with_holes() {
+    let grid = char_grid(
+        "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO",
+    );
+    let regions = grid.regions();
+    assert_eq!(regions.regions.len(), 5);
+
+    let o = regions.region_at(0, 0).unwrap();
+    assert_eq!((o.area, o.perimeter, o.sides), (21, 36, 20));
+
+    let total: usize = regions.regions.iter().map(|r| r.area * r.perimeter).sum();
+    assert_eq!(total, 772);
+    let total: usize = regions.regions.iter().map(|r| r.area * r.sides).sum();
+    assert_eq!(total, 436);
+}
+
+#[test]
+fn test_regions_diagonal_touch_is_separate() {
+    let grid = char_grid(
+        "AB
+BA",
+    );
+    let regions = grid.regions();
+    assert_eq!(regions.regions.len(), 4);
+    assert!(regions.regions.iter().all(|r| r.sides == 4));
+}
+
+#[test]
+fn test_regions_checkerboard() {
+    // every cell is its own region, which is slow if each region's fill has to start from scratch
+    let size = 200;
+    let grid = Grid::from_vec(
+        (0..size * size)
+            .map(|i| (i / size + i % size) % 2)
+            .collect(),
+        size,
+    );
+    let regions = grid.regions();
+    assert_eq!(regions.regions.len(), size * size);
+    assert!(regions.regions.iter().all(|r| r.area == 1));
+}
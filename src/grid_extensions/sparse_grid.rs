@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use grid::Grid;
+
+use super::Direction;
+
+/// A (row, col) position which may be negative or arbitrarily far away
+#[allow(dead_code)]
+pub type Point = (i64, i64);
+
+/// The smallest rectangle containing a set of points
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Bounds {
+    /// Inclusive
+    pub top_left: Point,
+    /// Inclusive
+    pub bottom_right: Point,
+}
+
+#[allow(dead_code)]
+impl Bounds {
+    fn of_point(p: Point) -> Bounds {
+        Bounds {
+            top_left: p,
+            bottom_right: p,
+        }
+    }
+
+    fn expand_to(&mut self, p: Point) {
+        self.top_left = (self.top_left.0.min(p.0), self.top_left.1.min(p.1));
+        self.bottom_right = (self.bottom_right.0.max(p.0), self.bottom_right.1.max(p.1));
+    }
+
+    fn on_edge(&self, p: Point) -> bool {
+        p.0 == self.top_left.0
+            || p.1 == self.top_left.1
+            || p.0 == self.bottom_right.0
+            || p.1 == self.bottom_right.1
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.top_left.0..=self.bottom_right.0).contains(&p.0)
+            && (self.top_left.1..=self.bottom_right.1).contains(&p.1)
+    }
+
+    /// (rows, cols) covered
+    pub fn size(&self) -> (usize, usize) {
+        (
+            (self.bottom_right.0 - self.top_left.0 + 1) as usize,
+            (self.bottom_right.1 - self.top_left.1 + 1) as usize,
+        )
+    }
+
+    /// Move a point into the bounds as if they wrapped round at the edges, like a torus
+    pub fn wrap(&self, p: Point) -> Point {
+        let (rows, cols) = self.size();
+        (
+            self.top_left.0 + (p.0 - self.top_left.0).rem_euclid(rows as i64),
+            self.top_left.1 + (p.1 - self.top_left.1).rem_euclid(cols as i64),
+        )
+    }
+}
+
+/// A grid with no fixed size which only stores the cells which have been set
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+        match self.bounds.as_mut() {
+            Some(b) => b.expand_to(p),
+            None => self.bounds = Some(Bounds::of_point(p)),
+        }
+        self.cells.insert(p, value)
+    }
+
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        let removed = self.cells.remove(&p);
+        if removed.is_some() && self.bounds.is_some_and(|b| b.on_edge(p)) {
+            // the bounds might have shrunk, so work them out again
+            self.bounds = None;
+            for p in self.cells.keys() {
+                match self.bounds.as_mut() {
+                    Some(b) => b.expand_to(*p),
+                    None => self.bounds = Some(Bounds::of_point(*p)),
+                }
+            }
+        }
+        removed
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest rectangle containing every set cell, or None if there aren't any
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Every set cell, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    /// The four points which share an edge with this one, whether they're set or not
+    pub fn orthogonal_neighbours(&self, p: Point) -> impl Iterator<Item = (Direction, Point)> {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .map(move |d| (d, d.step_unbounded(p)))
+    }
+
+    /// The eight points around this one, whether they're set or not
+    pub fn surrounding(&self, p: Point) -> Vec<Point> {
+        Direction::ALL
+            .into_iter()
+            .map(|d| d.step_unbounded(p))
+            .collect()
+    }
+
+    /// Take every cell of a dense grid whose value passes the filter, keeping the same coordinates
+    pub fn from_grid(grid: &Grid<T>, keep: impl Fn(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        let mut sparse = Self::new();
+        for ((row, col), v) in grid.indexed_iter() {
+            if keep(v) {
+                sparse.insert((row as i64, col as i64), v.clone());
+            }
+        }
+        sparse
+    }
+
+    /// Render into a dense grid covering `bounds()`, with `empty` in the unset cells.
+    /// Cell (0, 0) of the result is the top left of the bounds.
+    pub fn to_grid(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        match self.bounds {
+            Some(b) => self.to_grid_within(b, empty),
+            None => Grid::init(0, 0, empty),
+        }
+    }
+
+    /// Render the given area into a dense grid, with `empty` in the unset cells
+    pub fn to_grid_within(&self, bounds: Bounds, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let (rows, cols) = bounds.size();
+        let mut grid = Grid::init(rows, cols, empty);
+        for (p, v) in self.iter().filter(|(p, _)| bounds.contains(*p)) {
+            grid[(
+                (p.0 - bounds.top_left.0) as usize,
+                (p.1 - bounds.top_left.1) as usize,
+            )] = v.clone();
+        }
+        grid
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut sparse = Self::new();
+        for (p, v) in iter {
+            sparse.insert(p, v);
+        }
+        sparse
+    }
+}
+
+#[test]
+fn test_bounds_tracking() {
+    let mut sparse = SparseGrid::new();
+    assert_eq!(sparse.bounds(), None);
+
+    sparse.insert((0, 0), 'a');
+    sparse.insert((-3, 5), 'b');
+    sparse.insert((2, -1), 'c');
+    assert_eq!(
+        sparse.bounds(),
+        Some(Bounds {
+            top_left: (-3, -1),
+            bottom_right: (2, 5)
+        })
+    );
+    assert_eq!(sparse.bounds().unwrap().size(), (6, 7));
+
+    assert_eq!(sparse.remove((-3, 5)), Some('b'));
+    assert_eq!(
+        sparse.bounds(),
+        Some(Bounds {
+            top_left: (0, -1),
+            bottom_right: (2, 0)
+        })
+    );
+
+    sparse.remove((0, 0));
+    sparse.remove((2, -1));
+    assert!(sparse.is_empty());
+    assert_eq!(sparse.bounds(), None);
+}
+
+#[test]
+fn test_dense_round_trip() {
+    let grid = Grid::from_vec("..#.#....#..".chars().collect(), 4);
+    let sparse = SparseGrid::from_grid(&grid, |c| *c == '#');
+    assert_eq!(sparse.len(), 3);
+    assert!(sparse.contains((0, 2)));
+    assert!(sparse.contains((2, 1)));
+
+    // rendering only covers the bounds, so the empty last column goes missing
+    let rendered = sparse.to_grid('.');
+    assert_eq!(rendered.size(), (3, 3));
+    assert_eq!(
+        sparse.to_grid_within(
+            Bounds {
+                top_left: (0, 0),
+                bottom_right: (2, 3)
+            },
+            '.'
+        ),
+        grid
+    );
+}
+
+#[test]
+fn test_neighbours_off_grid() {
+    let sparse: SparseGrid<()> = [((0, 0), ())].into_iter().collect();
+    let neighbours = sparse.orthogonal_neighbours((0, 0)).collect::<Vec<_>>();
+    assert_eq!(
+        neighbours,
+        vec![
+            (Direction::North, (-1, 0)),
+            (Direction::East, (0, 1)),
+            (Direction::South, (1, 0)),
+            (Direction::West, (0, -1)),
+        ]
+    );
+    assert_eq!(sparse.surrounding((0, 0)).len(), 8);
+    assert!(sparse.surrounding((0, 0)).contains(&(-1, -1)));
+}
+
+#[test]
+fn test_wrap() {
+    let bounds = Bounds {
+        top_left: (0, 0),
+        bottom_right: (6, 10),
+    };
+    assert_eq!(bounds.wrap((3, 4)), (3, 4));
+    assert_eq!(bounds.wrap((-1, 11)), (6, 0));
+    assert_eq!(bounds.wrap((15, -23)), (1, 10));
+}
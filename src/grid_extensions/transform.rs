@@ -0,0 +1,199 @@
+use std::ops::Index;
+
+use grid::Grid;
+
+/// A rectangular window onto part of a grid, which borrows rather than copies the cells
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    top_left: (usize, usize),
+    size: (usize, usize),
+}
+
+// derive would demand T: Copy, but only the reference is copied
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+#[allow(dead_code)]
+impl<'a, T> GridView<'a, T> {
+    /// A view of the given size with its top left at the given position, or None if it doesn't fit inside the grid
+    pub fn new(
+        grid: &'a Grid<T>,
+        top_left: (usize, usize),
+        size: (usize, usize),
+    ) -> Option<GridView<'a, T>> {
+        if top_left.0 + size.0 > grid.rows() || top_left.1 + size.1 > grid.cols() {
+            None
+        } else {
+            Some(GridView {
+                grid,
+                top_left,
+                size,
+            })
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn rows(&self) -> usize {
+        self.size.0
+    }
+
+    pub fn cols(&self) -> usize {
+        self.size.1
+    }
+
+    /// Position in the underlying grid of the view's top left cell
+    pub fn top_left(&self) -> (usize, usize) {
+        self.top_left
+    }
+
+    /// Get a cell using coordinates relative to the view
+    pub fn get(&self, row: usize, col: usize) -> Option<&'a T> {
+        if row < self.size.0 && col < self.size.1 {
+            self.grid.get(self.top_left.0 + row, self.top_left.1 + col)
+        } else {
+            None
+        }
+    }
+
+    /// Every cell in row-major order, with coordinates relative to the view
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &'a T)> + use<'a, T> {
+        let view = *self;
+        (0..self.size.0)
+            .flat_map(move |row| (0..view.size.1).map(move |col| (row, col)))
+            .map(move |(row, col)| {
+                (
+                    (row, col),
+                    &view.grid[(view.top_left.0 + row, view.top_left.1 + col)],
+                )
+            })
+    }
+
+    /// A smaller view inside this one, positioned relative to this one
+    pub fn view(&self, top_left: (usize, usize), size: (usize, usize)) -> Option<GridView<'a, T>> {
+        if top_left.0 + size.0 > self.size.0 || top_left.1 + size.1 > self.size.1 {
+            None
+        } else {
+            GridView::new(
+                self.grid,
+                (self.top_left.0 + top_left.0, self.top_left.1 + top_left.1),
+                size,
+            )
+        }
+    }
+
+    /// Copy the viewed cells out into a grid of their own
+    pub fn to_grid(self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_vec(
+            self.indexed_iter().map(|(_, v)| v.clone()).collect(),
+            self.size.1,
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        self.get(row, col)
+            .unwrap_or_else(|| panic!("({row}, {col}) is outside a view of size {:?}", self.size))
+    }
+}
+
+/// The grid repeated `down` times vertically and `across` times horizontally
+#[allow(dead_code)]
+pub(super) fn tile<T: Clone>(grid: &Grid<T>, down: usize, across: usize) -> Grid<T> {
+    let (rows, cols) = grid.size();
+    let mut cells = Vec::with_capacity(rows * cols * down * across);
+    for row in 0..rows * down {
+        for col in 0..cols * across {
+            cells.push(grid[(row % rows, col % cols)].clone());
+        }
+    }
+    Grid::from_vec(cells, cols * across)
+}
+
+#[cfg(test)]
+use super::GridExtensions;
+#[cfg(test)]
+use grid::grid;
+
+#[cfg(test)]
+fn test_grid() -> Grid<u32> {
+    Grid::from_vec(vec![1, 2, 3, 4, 5, 6], 3)
+}
+
+#[test]
+fn test_rotation_round_trips() {
+    let grid = test_grid();
+    assert_eq!(
+        grid.rotated_90().rotated_90().rotated_90().rotated_90(),
+        grid
+    );
+    assert_eq!(grid.rotated_90().rotated_270(), grid);
+    assert_eq!(grid.rotated_270().rotated_90(), grid);
+    assert_eq!(grid.rotated_180().rotated_180(), grid);
+    assert_eq!(grid.rotated_90().rotated_90(), grid.rotated_180());
+    assert_eq!(grid.rotated_90().size(), (3, 2));
+}
+
+#[test]
+fn test_reflection_round_trips() {
+    let grid = test_grid();
+    assert_eq!(grid.transposed().transposed(), grid);
+    assert_eq!(grid.flipped_horizontal().flipped_horizontal(), grid);
+    assert_eq!(grid.flipped_vertical().flipped_vertical(), grid);
+    assert_eq!(
+        grid.flipped_horizontal().flipped_vertical(),
+        grid.rotated_180()
+    );
+    assert_eq!(grid.transposed().flipped_horizontal(), grid.rotated_90());
+    assert_eq!(grid.transposed(), Grid::from_vec(vec![1, 4, 2, 5, 3, 6], 2));
+}
+
+#[test]
+fn test_view() {
+    let grid = Grid::from_vec((0..16).collect(), 4);
+    let view = grid.view((1, 1), (2, 3)).unwrap();
+    assert_eq!(view.size(), (2, 3));
+    assert_eq!(view[(0, 0)], 5);
+    assert_eq!(view.get(1, 2), Some(&11));
+    assert_eq!(view.get(2, 0), None);
+    assert_eq!(view.to_grid(), Grid::from_vec(vec![5, 6, 7, 9, 10, 11], 3));
+    assert_eq!(
+        view.view((1, 1), (1, 2)).unwrap().to_grid(),
+        grid![[10, 11]]
+    );
+    assert!(view.view((1, 1), (2, 2)).is_none());
+    assert!(grid.view((3, 3), (2, 1)).is_none());
+    assert_eq!(grid.cropped((0, 0), (4, 4)), Some(grid.clone()));
+}
+
+#[test]
+fn test_tile_then_crop() {
+    let grid = test_grid();
+    let tiled = grid.tiled(2, 3);
+    assert_eq!(tiled.size(), (4, 9));
+    assert_eq!(tiled[(3, 8)], 6);
+    for down in 0..2 {
+        for across in 0..3 {
+            assert_eq!(
+                tiled.cropped((down * 2, across * 3), (2, 3)),
+                Some(grid.clone())
+            );
+        }
+    }
+    assert_eq!(grid.tiled(1, 1), grid);
+}
@@ -9,19 +9,21 @@ mod day6;
 mod day7;
 mod day9;
 mod grid_extensions;
+mod options;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut args = env::args();
 
     if let Some(day) = args.nth(1) {
         let daynum = u32::from_str(&day)?;
+        let options = args.collect::<Vec<String>>();
 
         match daynum {
-            1 => day1::run(),
-            2 => day2::run(),
-            3 => day3::run(),
-            4 => day4::run(),
-            5 => day5::run(),
+            1 => day1::run(&options),
+            2 => day2::run(&options),
+            3 => day3::run(&options),
+            4 => day4::run(&options),
+            5 => day5::run(&options),
             6 => day6::run(),
             7 => day7::run(),
             8 => {
@@ -0,0 +1,73 @@
+use std::{error::Error, str::FromStr};
+
+// Command line options for individual days, given after the day number, e.g. `1 --report --top 5`
+
+/// Was the flag given at all?
+pub fn has_flag(options: &[String], name: &str) -> bool {
+    options.iter().any(|o| o == name)
+}
+
+/// The value following the option, if the option was given
+pub fn value_of<'a>(options: &'a [String], name: &str) -> Result<Option<&'a str>, Box<dyn Error>> {
+    match options.iter().position(|o| o == name) {
+        Some(i) => match options.get(i + 1) {
+            Some(value) => Ok(Some(value.as_str())),
+            None => Err(format!("Option {} needs a value after it", name).into()),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The value following the option parsed into whatever type is wanted, if the option was given
+pub fn parsed_value_of<T>(options: &[String], name: &str) -> Result<Option<T>, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Error + 'static,
+{
+    match value_of(options, name)? {
+        Some(value) => {
+            Ok(Some(T::from_str(value).map_err(|e| {
+                format!("Option {} value '{}': {}", name, value, e)
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// For options whose value can be left out: None if the option wasn't given, otherwise the value following it
+/// unless that's another option
+pub fn optional_value_of<'a>(options: &'a [String], name: &str) -> Option<Option<&'a str>> {
+    let i = options.iter().position(|o| o == name)?;
+    Some(
+        options
+            .get(i + 1)
+            .map(|value| value.as_str())
+            .filter(|value| !value.starts_with("--")),
+    )
+}
+
+#[cfg(test)]
+fn options(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|o| o.to_owned()).collect()
+}
+
+#[test]
+fn test_options() {
+    let opts = options("--report --top 5 --csv pairs");
+    assert!(has_flag(&opts, "--report"));
+    assert!(!has_flag(&opts, "--explain"));
+    assert_eq!(value_of(&opts, "--csv").unwrap(), Some("pairs"));
+    assert_eq!(parsed_value_of::<usize>(&opts, "--top").unwrap(), Some(5));
+    assert_eq!(parsed_value_of::<usize>(&opts, "--buckets").unwrap(), None);
+    assert!(parsed_value_of::<usize>(&opts, "--csv").is_err());
+    assert!(value_of(&options("--top"), "--top").is_err());
+
+    let opts = options("--dot --mermaid 3");
+    assert_eq!(optional_value_of(&opts, "--dot"), Some(None));
+    assert_eq!(optional_value_of(&opts, "--mermaid"), Some(Some("3")));
+    assert_eq!(optional_value_of(&opts, "--analyse"), None);
+    assert_eq!(
+        optional_value_of(&options("--dot --top 5"), "--dot"),
+        Some(None)
+    );
+}